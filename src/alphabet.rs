@@ -0,0 +1,250 @@
+use std::fmt;
+
+use crate::{N, PAD_CHAR};
+
+/// Marker in the decode table for bytes that are not part of the alphabet.
+pub(crate) const INVALID_INDEX: u8 = 0xFF;
+
+/// The 64 symbols used by a base64 encoding, together with a reverse lookup
+/// table for decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Alphabet {
+    symbols: [u8; N as usize],
+    decode_table: [u8; 256],
+}
+
+/// Reasons a custom alphabet can be rejected by [`Alphabet::new`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlphabetError {
+    /// The alphabet does not contain exactly as many symbols as the encoding
+    /// needs, e.g. 64 for base64 (length returned).
+    InvalidLength(usize),
+    /// A symbol appears more than once in the alphabet (byte value returned).
+    DuplicatedByte(u8),
+    /// A symbol is not a printable ASCII character (byte value returned).
+    UnprintableByte(u8),
+    /// The padding character `=` was used as a symbol.
+    ReservedByte(u8),
+}
+
+impl fmt::Display for AlphabetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::InvalidLength(len) => {
+                write!(f, "alphabet has the wrong number of symbols ({len})")
+            }
+            Self::DuplicatedByte(byte) => {
+                write!(f, "symbol '{}' appears more than once", byte.escape_ascii())
+            }
+            Self::UnprintableByte(byte) => {
+                write!(f, "symbol '{}' is not printable ASCII", byte.escape_ascii())
+            }
+            Self::ReservedByte(byte) => {
+                write!(
+                    f,
+                    "symbol '{}' is reserved for padding",
+                    byte.escape_ascii()
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for AlphabetError {}
+
+/// Unwrap an alphabet in a const context, failing compilation on error.
+const fn expect_alphabet(result: Result<Alphabet, AlphabetError>) -> Alphabet {
+    match result {
+        Ok(alphabet) => alphabet,
+        Err(_) => panic!("invalid built-in alphabet"),
+    }
+}
+
+impl Alphabet {
+    /// The standard alphabet from RFC 4648 §4.
+    pub const STANDARD: Alphabet = expect_alphabet(Alphabet::new(
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/",
+    ));
+
+    /// The URL and filename safe alphabet from RFC 4648 §5.
+    pub const URL_SAFE: Alphabet = expect_alphabet(Alphabet::new(
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_",
+    ));
+
+    /// The alphabet of IMAP mailbox names from RFC 3501 §5.1.3, which is the
+    /// standard alphabet with `,` instead of `/`.
+    pub const IMAP: Alphabet = expect_alphabet(Alphabet::new(
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+,",
+    ));
+
+    /// Build an alphabet from 64 unique printable ASCII symbols.
+    /// The symbol at position `i` encodes the 6-bit value `i`.
+    pub const fn new(symbols: &[u8]) -> Result<Self, AlphabetError> {
+        let decode_table = match build_decode_table(symbols, N as usize, &[PAD_CHAR]) {
+            Ok(decode_table) => decode_table,
+            Err(err) => return Err(err),
+        };
+        let mut table = [0u8; N as usize];
+        table.copy_from_slice(symbols);
+        Ok(Self {
+            symbols: table,
+            decode_table,
+        })
+    }
+
+    /// The symbols of this alphabet, ordered by the 6-bit value they encode.
+    pub const fn symbols(&self) -> &[u8; N as usize] {
+        &self.symbols
+    }
+
+    /// Get the index of input base64 character in this alphabet.
+    /// The returned index is in the range `[0, 63]`.
+    /// If the input character is not in the alphabet, return None.
+    pub const fn index_of(&self, input_char: u8) -> Option<u8> {
+        match self.decode_table[input_char as usize] {
+            INVALID_INDEX => None,
+            index => Some(index),
+        }
+    }
+}
+
+/// Validate `len` unique printable ASCII symbols, none of which is one of the
+/// `reserved` bytes, and build the table mapping each symbol to its position.
+pub(crate) const fn build_decode_table(
+    symbols: &[u8],
+    len: usize,
+    reserved: &[u8],
+) -> Result<[u8; 256], AlphabetError> {
+    if symbols.len() != len {
+        return Err(AlphabetError::InvalidLength(symbols.len()));
+    }
+
+    let mut decode_table = [INVALID_INDEX; 256];
+    let mut i = 0;
+    while i < symbols.len() {
+        let symbol = symbols[i];
+        if !symbol.is_ascii_graphic() {
+            return Err(AlphabetError::UnprintableByte(symbol));
+        }
+        let mut j = 0;
+        while j < reserved.len() {
+            if symbol == reserved[j] {
+                return Err(AlphabetError::ReservedByte(symbol));
+            }
+            j += 1;
+        }
+        if decode_table[symbol as usize] != INVALID_INDEX {
+            return Err(AlphabetError::DuplicatedByte(symbol));
+        }
+        decode_table[symbol as usize] = i as u8;
+        i += 1;
+    }
+    Ok(decode_table)
+}
+
+impl Default for Alphabet {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_table() {
+        let table = Alphabet::STANDARD.symbols();
+        let mut i = 0;
+
+        // Uppercase alphabet
+        for c in b'A'..=b'Z' {
+            assert_eq!(table[i], c);
+            i += 1;
+        }
+
+        // Lowercase alphabet
+        for c in b'a'..=b'z' {
+            assert_eq!(table[i], c);
+            i += 1;
+        }
+
+        // Digits
+        for c in b'0'..=b'9' {
+            assert_eq!(table[i], c);
+            i += 1;
+        }
+
+        // '+' and '/'
+        for c in b"+/" {
+            assert_eq!(table[i], *c);
+            i += 1;
+        }
+    }
+
+    #[test]
+    fn test_index_of_valid_chars() {
+        let alphabet = Alphabet::STANDARD;
+        let mut i = 0u8;
+
+        // Uppercase alphabet
+        for c in b'A'..=b'Z' {
+            assert_eq!(alphabet.index_of(c).unwrap(), i);
+            i += 1;
+        }
+
+        // Lowercase alphabet
+        for c in b'a'..=b'z' {
+            assert_eq!(alphabet.index_of(c).unwrap(), i);
+            i += 1;
+        }
+
+        // Digits
+        for c in b'0'..=b'9' {
+            assert_eq!(alphabet.index_of(c).unwrap(), i);
+            i += 1;
+        }
+
+        assert_eq!(alphabet.index_of(b'+').unwrap(), 62);
+        assert_eq!(alphabet.index_of(b'/').unwrap(), 63);
+    }
+
+    #[test]
+    fn test_index_of_invalid_chars() {
+        let invalid_chars = [b'=', b'!', b' ', b'\n', b'-', b'@', b'[', b'`', b'{', 255u8];
+        for &c in &invalid_chars {
+            assert!(Alphabet::STANDARD.index_of(c).is_none());
+        }
+    }
+
+    #[test]
+    fn test_url_safe_table() {
+        let alphabet = Alphabet::URL_SAFE;
+        assert_eq!(alphabet.index_of(b'-'), Some(62));
+        assert_eq!(alphabet.index_of(b'_'), Some(63));
+        assert!(alphabet.index_of(b'+').is_none());
+        assert!(alphabet.index_of(b'/').is_none());
+    }
+
+    #[test]
+    fn test_custom_alphabet_validation() {
+        assert_eq!(Alphabet::new(b"ABC"), Err(AlphabetError::InvalidLength(3)));
+        assert_eq!(
+            Alphabet::new(b"AACDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/"),
+            Err(AlphabetError::DuplicatedByte(b'A'))
+        );
+        assert_eq!(
+            Alphabet::new(b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+="),
+            Err(AlphabetError::ReservedByte(b'='))
+        );
+        assert_eq!(
+            Alphabet::new(b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+ "),
+            Err(AlphabetError::UnprintableByte(b' '))
+        );
+
+        let reversed: Vec<u8> = Alphabet::STANDARD.symbols().iter().rev().copied().collect();
+        let alphabet = Alphabet::new(&reversed).unwrap();
+        assert_eq!(alphabet.index_of(b'/'), Some(0));
+        assert_eq!(alphabet.index_of(b'A'), Some(63));
+    }
+}
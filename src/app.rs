@@ -6,8 +6,10 @@ use crossterm::{ExecutableCommand, cursor, event, queue, style, terminal};
 
 use std::io::{Stdout, Write};
 
-use crate::decode::{DecodeError, decode_string};
-use crate::encode::encode_string;
+use base64::{
+    Base16, Base32, Base45, Base58, DecodeError, Engine, armor, data_url, decode_string,
+    encode_string, pem,
+};
 
 /// Set a panic hook to restore terminal state on panic
 /// This ensures that the terminal is not left in raw mode or alternate screen on panic
@@ -22,6 +24,7 @@ fn set_panic_hook() {
 }
 
 /// Setup terminal in raw mode and enter alternate screen
+/// Bracketed paste is enabled so pasted line breaks arrive as part of the paste
 /// Also sets a panic hook to restore terminal on panic
 pub fn setup_terminal(stdout: &mut Stdout) -> std::io::Result<()> {
     terminal::enable_raw_mode()?;
@@ -29,6 +32,7 @@ pub fn setup_terminal(stdout: &mut Stdout) -> std::io::Result<()> {
     crossterm::queue!(
         stdout,
         terminal::EnterAlternateScreen,
+        event::EnableBracketedPaste,
         terminal::Clear(ClearType::All),
         cursor::Hide,
         cursor::MoveTo(0, 0)
@@ -38,14 +42,193 @@ pub fn setup_terminal(stdout: &mut Stdout) -> std::io::Result<()> {
 }
 
 /// Restore terminal to original state
-/// Leave alternate screen and disable raw mode
+/// Leave alternate screen, disable bracketed paste and raw mode
 pub fn restore_terminal(stdout: &mut Stdout) -> std::io::Result<()> {
-    queue!(stdout, terminal::LeaveAlternateScreen, cursor::Show)?;
+    queue!(
+        stdout,
+        event::DisableBracketedPaste,
+        terminal::LeaveAlternateScreen,
+        cursor::Show
+    )?;
     stdout.flush()?;
     terminal::disable_raw_mode()?;
     Ok(())
 }
 
+/// Make input printable on a single line in raw mode
+/// Line breaks are shown as "↵" instead of moving the cursor
+fn printable(text: &str) -> String {
+    text.replace("\r\n", "\n").replace(['\r', '\n'], "↵")
+}
+
+/// Print a labelled encoded line, reversed when it has focus
+fn print_encoded_line(
+    stdout: &mut Stdout,
+    label: &str,
+    text: &str,
+    focused: bool,
+) -> std::io::Result<()> {
+    queue!(
+        stdout,
+        style::PrintStyledContent(label.with(Color::Green).attribute(Attribute::Bold)),
+    )?;
+    if focused {
+        queue!(stdout, style::SetAttribute(Attribute::Reverse))?;
+    }
+    queue!(stdout, style::Print(text.with(Color::Yellow)))?;
+    if focused {
+        queue!(stdout, style::SetAttribute(Attribute::NoReverse))?;
+    }
+    queue!(stdout, style::Print(" \r\n"))?;
+    stdout.flush()
+}
+
+/// The decoded line for the current input
+struct Decoded {
+    label: String,
+    result: Result<String, String>,
+    /// Byte offset in the input of the symbol that failed to decode
+    error_offset: Option<usize>,
+}
+
+/// The encoding the decoded line reads the input as, cycled with F3
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Base64,
+    Base32,
+    Base45,
+    Hex,
+    Base58,
+    Base58Check,
+}
+
+impl Format {
+    fn next(self) -> Self {
+        match self {
+            Format::Base64 => Format::Base32,
+            Format::Base32 => Format::Base45,
+            Format::Base45 => Format::Hex,
+            Format::Hex => Format::Base58,
+            Format::Base58 => Format::Base58Check,
+            Format::Base58Check => Format::Base64,
+        }
+    }
+}
+
+/// Show decoded text as is and binary data escaped
+fn display_bytes(bytes: Vec<u8>) -> String {
+    match String::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(err) => err.as_bytes().escape_ascii().to_string(),
+    }
+}
+
+/// Decode the input in the selected format
+fn decode_input(
+    input: &str,
+    engine: &Engine,
+    format: Format,
+    strict: bool,
+    multiline: bool,
+) -> Decoded {
+    let (name, decoded) = match format {
+        Format::Base64 => return decode_base64(input, engine, strict, multiline),
+        Format::Base32 => {
+            let base32 = Base32::STANDARD
+                .with_canonical(strict)
+                .with_ignore_whitespace(multiline);
+            ("Base32", base32.decode(input.as_bytes()))
+        }
+        Format::Base45 => ("Base45", Base45::STANDARD.decode(input.as_bytes())),
+        Format::Hex => ("Hex", Base16::LOWER.decode(input.as_bytes())),
+        Format::Base58 => ("Base58", Base58::BITCOIN.decode(input.as_bytes())),
+        Format::Base58Check => (
+            "Base58Check",
+            Base58::BITCOIN.decode_check(input.as_bytes()),
+        ),
+    };
+    Decoded {
+        label: format!("{name} Decoded: "),
+        error_offset: decoded.as_ref().err().map(DecodeError::offset),
+        result: decoded.map(display_bytes).map_err(|err| err.to_string()),
+    }
+}
+
+/// Decode the input as a data URL, OpenPGP armor or PEM block if it looks like
+/// one, or as plain base64 otherwise
+fn decode_base64(input: &str, engine: &Engine, strict: bool, multiline: bool) -> Decoded {
+    if input
+        .get(..5)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("data:"))
+    {
+        return match data_url::parse(input) {
+            Ok(url) => {
+                let mut media_type = url.media_type().to_string();
+                for (name, value) in url.parameters() {
+                    media_type.push_str(&format!(";{name}={value}"));
+                }
+                Decoded {
+                    label: format!("Data URL Decoded ({media_type}): "),
+                    result: Ok(display_bytes(url.into_data())),
+                    error_offset: None,
+                }
+            }
+            Err(err) => Decoded {
+                label: "Data URL Decoded: ".to_string(),
+                error_offset: Some(err.offset()),
+                result: Err(err.to_string()),
+            },
+        };
+    }
+    if input.contains("-----BEGIN PGP ") {
+        return match armor::parse(input) {
+            // OpenPGP packets are binary, so show them escaped
+            Ok(block) => Decoded {
+                label: format!("{} Decoded ({} bytes): ", block.label(), block.data().len()),
+                result: Ok(block.data().escape_ascii().to_string()),
+                error_offset: None,
+            },
+            Err(err) => Decoded {
+                label: "PGP Armor Decoded: ".to_string(),
+                error_offset: err.offset(),
+                result: Err(err.to_string()),
+            },
+        };
+    }
+    if input.contains("-----BEGIN ") {
+        return match pem::parse(input) {
+            // PEM bodies are usually binary DER, so show them escaped
+            Ok(block) => Decoded {
+                label: format!(
+                    "PEM {} Decoded ({} bytes): ",
+                    block.label(),
+                    block.contents().len()
+                ),
+                result: Ok(block.contents().escape_ascii().to_string()),
+                error_offset: None,
+            },
+            Err(err) => Decoded {
+                label: "PEM Decoded: ".to_string(),
+                error_offset: err.offset(),
+                result: Err(err.to_string()),
+            },
+        };
+    }
+
+    let label = match (strict, multiline) {
+        (false, false) => "Base64 Decoded: ",
+        (true, false) => "Base64 Decoded (strict): ",
+        (false, true) => "Base64 Decoded (multi-line): ",
+        (true, true) => "Base64 Decoded (strict, multi-line): ",
+    };
+    let decoded = decode_string(input, engine);
+    Decoded {
+        label: label.to_string(),
+        error_offset: decoded.as_ref().err().map(DecodeError::offset),
+        result: decoded.map_err(|err| err.to_string()),
+    }
+}
+
 pub fn run(stdout: &mut Stdout) -> std::io::Result<()> {
     // Title
     stdout.execute(style::PrintStyledContent(
@@ -56,13 +239,18 @@ pub fn run(stdout: &mut Stdout) -> std::io::Result<()> {
 
     let mut input = String::new();
 
-    let mut status_line =
-        "Use Up/Down to switch focus, Enter to copy focused line to clipboard, Esc to exit.\r\n";
+    let mut status_line = "Use Up/Down to switch focus, Enter to copy focused line to clipboard, F2 to toggle strict decoding, F3 to switch decoding format, Esc to exit.\r\n";
 
-    #[derive(PartialEq, Eq)]
+    // Strict decoding rejects encodings with non-zero trailing bits
+    let mut strict = false;
+
+    let mut format = Format::Base64;
+
+    #[derive(Clone, Copy, PartialEq, Eq)]
     enum Focus {
         Input,
-        Encoded,
+        /// Index into the encoded lines
+        Encoded(usize),
         Decoded,
     }
 
@@ -73,6 +261,13 @@ pub fn run(stdout: &mut Stdout) -> std::io::Result<()> {
     stdout.flush()?;
 
     loop {
+        // Pasted multi-line input (e.g. wrapped MIME or PEM bodies) is decoded
+        // with line breaks and other whitespace skipped
+        let multiline = input.contains(['\n', '\r']);
+        let engine = Engine::STANDARD
+            .with_canonical(strict)
+            .with_ignore_whitespace(multiline);
+
         // Re-render
         queue!(
             stdout,
@@ -90,6 +285,10 @@ pub fn run(stdout: &mut Stdout) -> std::io::Result<()> {
             ),
         )?;
 
+        // Decode first so the input line can point at the failing symbol
+        let decoded = decode_input(&input, &engine, format, strict, multiline);
+        let error_offset = decoded.error_offset;
+
         // Print prompt and input
         queue!(
             stdout,
@@ -103,8 +302,33 @@ pub fn run(stdout: &mut Stdout) -> std::io::Result<()> {
         if focus == Focus::Input {
             queue!(stdout, style::SetAttribute(Attribute::Reverse))?;
         }
-        queue!(stdout, style::Print(&input))?;
-        queue!(stdout, style::Print("⏎"))?;
+        match error_offset {
+            // Highlight the symbol that failed to decode
+            Some(offset) if offset < input.len() && input.is_char_boundary(offset) => {
+                let (before, rest) = input.split_at(offset);
+                let mut rest = rest.chars();
+                let failed = rest.next().map(String::from).unwrap_or_default();
+                queue!(
+                    stdout,
+                    style::Print(printable(before)),
+                    style::PrintStyledContent(failed.on(Color::Red)),
+                    style::Print(printable(rest.as_str())),
+                    style::Print("⏎"),
+                )?;
+            }
+            // Errors at the end of the input, e.g. missing padding
+            Some(offset) if offset >= input.len() => {
+                queue!(
+                    stdout,
+                    style::Print(printable(&input)),
+                    style::PrintStyledContent("⏎".on(Color::Red)),
+                )?;
+            }
+            _ => {
+                queue!(stdout, style::Print(printable(&input)))?;
+                queue!(stdout, style::Print("⏎"))?;
+            }
+        }
         if focus == Focus::Input {
             queue!(stdout, style::SetAttribute(Attribute::NoReverse))?;
         }
@@ -112,47 +336,36 @@ pub fn run(stdout: &mut Stdout) -> std::io::Result<()> {
         queue!(stdout, style::Print("\r\n"))?;
         stdout.flush()?;
 
-        // Print encoded string
-        let encoded = encode_string(&input);
-        // Encoded line: show focus and persistent highlight
-        queue!(
-            stdout,
-            style::PrintStyledContent(
-                "Base64 Encoded: "
-                    .with(Color::Green)
-                    .attribute(Attribute::Bold),
+        // Print encoded strings, one line per encoding
+        let encoded_lines = [
+            ("Base64 Encoded: ", encode_string(&input, &engine)),
+            (
+                "Base32 Encoded: ",
+                Base32::STANDARD.encode(input.as_bytes()),
             ),
-        )?;
-
-        if focus == Focus::Encoded {
-            queue!(stdout, style::SetAttribute(Attribute::Reverse))?;
-        }
-        queue!(stdout, style::Print(&encoded.with(Color::Yellow)))?;
-        if focus == Focus::Encoded {
-            queue!(stdout, style::SetAttribute(Attribute::NoReverse))?;
+            (
+                "Base45 Encoded: ",
+                Base45::STANDARD.encode(input.as_bytes()),
+            ),
+            ("Hex Encoded: ", Base16::LOWER.encode(input.as_bytes())),
+            ("Base58 Encoded: ", Base58::BITCOIN.encode(input.as_bytes())),
+        ];
+        for (i, (label, text)) in encoded_lines.iter().enumerate() {
+            print_encoded_line(stdout, label, text, focus == Focus::Encoded(i))?;
         }
-        queue!(stdout, style::Print(" \r\n"))?;
-        stdout.flush()?;
 
         // Print decoded string
-        let decoded = decode_string(&input);
-
-        let displayed_decoded = match decoded {
-            Ok(s) => s.with(Color::Yellow),
-            Err(err) => {
-                let msg = match err {
-                    DecodeError::WrongPadding => "<Invalid padding>".to_string(),
-                    DecodeError::InputLength => "<Invalid input length>".to_string(),
-                    DecodeError::InvalidByte(_) => "<Input contains invalid byte>".to_string(),
-                };
-                msg.with(Color::Red)
-            }
+        let displayed_decoded = match &decoded.result {
+            Ok(s) => s.clone().with(Color::Yellow),
+            Err(err) => format!("<{err}>").with(Color::Red),
         };
         // Decoded line: show focus and persistent highlight
         queue!(
             stdout,
             style::PrintStyledContent(
-                "Base64 Decoded: "
+                decoded
+                    .label
+                    .as_str()
                     .with(Color::Green)
                     .attribute(Attribute::Bold),
             ),
@@ -175,16 +388,18 @@ pub fn run(stdout: &mut Stdout) -> std::io::Result<()> {
                 ..
             }) => {
                 match code {
-                    KeyCode::Char(c) => {
-                        // Only edit input when input line is focused
-                        if focus == Focus::Input {
-                            input.push(c);
-                        }
+                    // Only edit input when input line is focused
+                    KeyCode::Char(c) if focus == Focus::Input => {
+                        input.push(c);
                     }
-                    KeyCode::Backspace => {
-                        if focus == Focus::Input {
-                            input.pop();
-                        }
+                    KeyCode::Backspace if focus == Focus::Input => {
+                        input.pop();
+                    }
+                    KeyCode::F(2) => {
+                        strict = !strict;
+                    }
+                    KeyCode::F(3) => {
+                        format = format.next();
                     }
                     KeyCode::Esc => {
                         // User cancelled input. Exit loop.
@@ -193,8 +408,9 @@ pub fn run(stdout: &mut Stdout) -> std::io::Result<()> {
                     KeyCode::Up => {
                         focus = match focus {
                             Focus::Input => Focus::Decoded,
-                            Focus::Encoded => Focus::Input,
-                            Focus::Decoded => Focus::Encoded,
+                            Focus::Encoded(0) => Focus::Input,
+                            Focus::Encoded(i) => Focus::Encoded(i - 1),
+                            Focus::Decoded => Focus::Encoded(encoded_lines.len() - 1),
                         }
                     }
                     KeyCode::Enter => {
@@ -207,12 +423,12 @@ pub fn run(stdout: &mut Stdout) -> std::io::Result<()> {
                         // Copy currently focused line to clipboard using crossterm clipboard support
                         let is_err = match focus {
                             Focus::Input => stdout.execute(cmd(input.clone())).is_err(),
-                            Focus::Encoded => {
-                                let encoded = encode_string(&input);
-                                stdout.execute(cmd(encoded)).is_err()
+                            Focus::Encoded(i) => {
+                                let (_, encoded) = &encoded_lines[i];
+                                stdout.execute(cmd(encoded.clone())).is_err()
                             }
-                            Focus::Decoded => match decode_string(&input) {
-                                Ok(s) => stdout.execute(cmd(s.clone())).is_err(),
+                            Focus::Decoded => match decoded.result {
+                                Ok(s) => stdout.execute(cmd(s)).is_err(),
                                 Err(_) => false,
                             },
                         };
@@ -225,19 +441,20 @@ pub fn run(stdout: &mut Stdout) -> std::io::Result<()> {
                     }
                     KeyCode::Down => {
                         focus = match focus {
-                            Focus::Input => Focus::Encoded,
-                            Focus::Encoded => Focus::Decoded,
+                            Focus::Input => Focus::Encoded(0),
+                            Focus::Encoded(i) if i + 1 < encoded_lines.len() => {
+                                Focus::Encoded(i + 1)
+                            }
+                            Focus::Encoded(_) => Focus::Decoded,
                             Focus::Decoded => Focus::Input,
                         }
                     }
                     _ => {}
                 }
             }
-            event::Event::Paste(content) => {
-                // On paste event, insert content at cursor if input line is focused
-                if focus == Focus::Input {
-                    input.push_str(&content);
-                }
+            // On paste event, insert content at cursor if input line is focused
+            event::Event::Paste(content) if focus == Focus::Input => {
+                input.push_str(&content);
             }
             _ => {}
         }
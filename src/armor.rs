@@ -0,0 +1,351 @@
+//! OpenPGP ASCII Armor following
+//! [RFC 4880 §6](https://datatracker.ietf.org/doc/html/rfc4880#section-6).
+//!
+//! Armor wraps base64-encoded OpenPGP data between `-----BEGIN PGP ...-----`
+//! and `-----END PGP ...-----` lines, like PEM. It adds `Key: Value` armor
+//! headers followed by an empty line, and an optional `=XXXX` line after the
+//! body holding the base64-encoded CRC-24 of the decoded data, which catches
+//! corruption in transport.
+
+use std::fmt;
+
+use crate::decode::DecodeError;
+use crate::engine::Engine;
+use crate::pem::{BEGIN, DASHES, END, boundary_label, lines};
+
+/// Initial value of the CRC-24 register.
+const CRC24_INIT: u32 = 0xB7_04CE;
+
+/// The CRC-24 generator polynomial.
+const CRC24_POLY: u32 = 0x186_4CFB;
+
+/// Label of a clearsigned message, whose cleartext is not base64.
+const SIGNED_MESSAGE: &str = "PGP SIGNED MESSAGE";
+
+/// A single armored block: its label, armor headers and decoded data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Armor {
+    label: String,
+    headers: Vec<(String, String)>,
+    data: Vec<u8>,
+}
+
+impl Armor {
+    /// Create a block with the given label (e.g. `PGP PUBLIC KEY BLOCK`) and data.
+    pub fn new(label: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            label: label.into(),
+            headers: Vec::new(),
+            data: data.into(),
+        }
+    }
+
+    /// Add an armor header such as `Comment`, emitted in insertion order.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// The label from the `BEGIN` and `END` lines.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// The armor headers as `(name, value)` pairs, in order of appearance.
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// The decoded data.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consume the block and return the decoded data.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+/// Writes the block as armored text, with the body wrapped at 64 columns,
+/// followed by its checksum line. Every line is terminated by LF.
+impl fmt::Display for Armor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{BEGIN}{}{DASHES}", self.label)?;
+        for (name, value) in &self.headers {
+            writeln!(f, "{name}: {value}")?;
+        }
+        writeln!(f)?;
+        f.write_str(&Engine::PEM.encode(&self.data))?;
+        let checksum = crc24(&self.data).to_be_bytes();
+        writeln!(f, "={}", Engine::STANDARD.encode(&checksum[1..]))?;
+        writeln!(f, "{END}{}{DASHES}", self.label)
+    }
+}
+
+/// Every variant except [`ArmorError::MissingBegin`] carries the byte offset
+/// in the input where the problem was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArmorError {
+    /// The input contains no `-----BEGIN` line.
+    MissingBegin,
+    /// A `BEGIN` or `END` line is malformed. The offset points at the start
+    /// of the line.
+    InvalidBoundary { offset: usize },
+    /// The block starting at `offset` has no matching `END` line.
+    MissingEnd { offset: usize },
+    /// The label of the `END` line at `offset` differs from the `BEGIN` label.
+    LabelMismatch { offset: usize },
+    /// An armor header line is malformed, or the headers are not followed by
+    /// an empty line. The offset points at the start of the line.
+    InvalidHeader { offset: usize },
+    /// The body or checksum line is not valid base64, or the checksum does not
+    /// match the data ([`DecodeError::InvalidChecksum`]). Offsets are relative
+    /// to the whole input.
+    Decode(DecodeError),
+}
+
+impl ArmorError {
+    /// The byte offset in the input where the problem was found, if any.
+    pub const fn offset(&self) -> Option<usize> {
+        match *self {
+            Self::MissingBegin => None,
+            Self::InvalidBoundary { offset }
+            | Self::MissingEnd { offset }
+            | Self::LabelMismatch { offset }
+            | Self::InvalidHeader { offset } => Some(offset),
+            Self::Decode(ref err) => Some(err.offset()),
+        }
+    }
+}
+
+impl fmt::Display for ArmorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingBegin => f.write_str("no armored block found"),
+            Self::InvalidBoundary { offset } => {
+                write!(f, "invalid armor boundary line at offset {offset}")
+            }
+            Self::MissingEnd { offset } => {
+                write!(f, "armored block at offset {offset} has no END line")
+            }
+            Self::LabelMismatch { offset } => {
+                write!(
+                    f,
+                    "armor END label at offset {offset} does not match BEGIN label"
+                )
+            }
+            Self::InvalidHeader { offset } => {
+                write!(f, "invalid armor header at offset {offset}")
+            }
+            Self::Decode(err) => write!(f, "invalid armor body: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ArmorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Decode(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Parse the first armored block in `input`, ignoring any text around it.
+/// The checksum is verified if present. The cleartext of a clearsigned
+/// message is skipped, so parsing one returns its `PGP SIGNATURE` block.
+pub fn parse(input: &str) -> Result<Armor, ArmorError> {
+    let mut lines = lines(input);
+
+    // Skip text and clearsigned cleartext before the block
+    let (begin_offset, label) = loop {
+        let Some((offset, line)) = lines.next() else {
+            return Err(ArmorError::MissingBegin);
+        };
+        if let Some(label) = boundary_label(line, offset, BEGIN) {
+            let label = label.map_err(|_| ArmorError::InvalidBoundary { offset })?;
+            if label != SIGNED_MESSAGE {
+                break (offset, label);
+            }
+        }
+    };
+
+    let mut headers: Vec<(String, String)> = Vec::new();
+    let mut in_headers = None;
+    let mut body_start = None;
+    let mut checksum = None;
+    let end_offset = loop {
+        let Some((offset, line)) = lines.next() else {
+            return Err(ArmorError::MissingEnd {
+                offset: begin_offset,
+            });
+        };
+        if let Some(end_label) = boundary_label(line, offset, END) {
+            if end_label.map_err(|_| ArmorError::InvalidBoundary { offset })? != label {
+                return Err(ArmorError::LabelMismatch { offset });
+            }
+            if in_headers == Some(true) {
+                // Headers must be followed by an empty line and the body
+                return Err(ArmorError::InvalidHeader { offset });
+            }
+            break offset;
+        }
+        if let Some((checksum_offset, _)) = checksum {
+            // Only the END line may follow the checksum
+            return Err(ArmorError::Decode(DecodeError::InvalidByte {
+                offset: checksum_offset,
+                byte: b'=',
+            }));
+        }
+
+        // Base64 never contains `:`, so headers are detected on the first line
+        let in_headers = in_headers.get_or_insert(line.contains(':'));
+        if !*in_headers {
+            // The checksum is `=` and 4 symbols, which a body wrapped at a
+            // multiple of 4 columns never starts a line with
+            let trimmed = line.trim_end();
+            if trimmed.len() == 5
+                && let Some(encoded) = trimmed.strip_prefix('=')
+            {
+                checksum = Some((offset, encoded));
+                continue;
+            }
+            body_start.get_or_insert(offset);
+        } else if line.trim().is_empty() {
+            *in_headers = false;
+        } else {
+            let (name, value) = line
+                .split_once(": ")
+                .filter(|(name, _)| !name.is_empty() && !name.contains(char::is_whitespace))
+                .ok_or(ArmorError::InvalidHeader { offset })?;
+            headers.push((name.to_string(), value.trim().to_string()));
+        }
+    };
+
+    let body_end = checksum.map_or(end_offset, |(offset, _)| offset);
+    let body_start = body_start.unwrap_or(body_end);
+    let data = Engine::PEM
+        .decode(&input.as_bytes()[body_start..body_end])
+        .map_err(|err| ArmorError::Decode(err.map_offset(|offset| body_start + offset)))?;
+
+    if let Some((offset, encoded)) = checksum {
+        let decoded = Engine::STANDARD
+            .decode(encoded.as_bytes())
+            .map_err(|err| ArmorError::Decode(err.map_offset(|o| offset + 1 + o)))?;
+        let expected = crc24(&data).to_be_bytes();
+        if decoded != expected[1..] {
+            return Err(ArmorError::Decode(DecodeError::InvalidChecksum { offset }));
+        }
+    }
+
+    Ok(Armor {
+        label: label.to_string(),
+        headers,
+        data,
+    })
+}
+
+/// Armor `data` with the given label and a checksum line.
+pub fn encode(label: &str, data: &[u8]) -> String {
+    Armor::new(label, data).to_string()
+}
+
+/// Compute the CRC-24 of `data` used by the armor checksum line.
+pub fn crc24(data: &[u8]) -> u32 {
+    let mut crc = CRC24_INIT;
+    for &byte in data {
+        crc ^= (byte as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x100_0000 != 0 {
+                crc ^= CRC24_POLY;
+            }
+        }
+    }
+    crc & 0xFF_FFFF
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc24() {
+        assert_eq!(crc24(b""), 0xB7_04CE);
+        assert_eq!(crc24(b"123456789"), 0x21_CF02);
+    }
+
+    #[test]
+    fn test_encode_and_parse() {
+        let armor = Armor::new("PGP MESSAGE", *b"Hello").with_header("Comment", "test only");
+        let encoded = armor.to_string();
+        assert_eq!(
+            encoded,
+            "-----BEGIN PGP MESSAGE-----\n\
+             Comment: test only\n\
+             \n\
+             SGVsbG8=\n\
+             =EHJM\n\
+             -----END PGP MESSAGE-----\n"
+        );
+        assert_eq!(parse(&encoded).unwrap(), armor);
+
+        // Headers, checksum and line breaks are all optional when parsing
+        let input = "junk\r\n-----BEGIN PGP SIGNATURE-----\r\nSGVs\r\nbG8=\r\n-----END PGP SIGNATURE-----\r\n";
+        assert_eq!(parse(input).unwrap().data(), b"Hello");
+        assert_eq!(parse(&encode("PGP MESSAGE", b"")).unwrap().data(), b"");
+    }
+
+    #[test]
+    fn test_parse_clearsigned() {
+        let input = "-----BEGIN PGP SIGNED MESSAGE-----\n\
+                     Hash: SHA256\n\
+                     \n\
+                     Not base64: at all\n\
+                     - -----BEGIN dash-escaped-----\n\
+                     -----BEGIN PGP SIGNATURE-----\n\
+                     \n\
+                     MTIzNDU2Nzg5\n\
+                     =Ic8C\n\
+                     -----END PGP SIGNATURE-----\n";
+        let armor = parse(input).unwrap();
+        assert_eq!(armor.label(), "PGP SIGNATURE");
+        assert_eq!(armor.data(), b"123456789");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("nothing"), Err(ArmorError::MissingBegin));
+        assert_eq!(
+            parse("-----BEGIN PGP MESSAGE-----\n\nSGVsbG8=\n=EHJN\n-----END PGP MESSAGE-----\n"),
+            Err(ArmorError::Decode(DecodeError::InvalidChecksum {
+                offset: 38
+            }))
+        );
+        assert_eq!(
+            parse("-----BEGIN PGP MESSAGE-----\n\nSGVsbG8=\n=EH!M\n-----END PGP MESSAGE-----\n"),
+            Err(ArmorError::Decode(DecodeError::InvalidByte {
+                offset: 41,
+                byte: b'!'
+            }))
+        );
+        assert_eq!(
+            parse(
+                "-----BEGIN PGP MESSAGE-----\nComment:test\n\nSGVsbG8=\n-----END PGP MESSAGE-----\n"
+            ),
+            Err(ArmorError::InvalidHeader { offset: 28 })
+        );
+        assert_eq!(
+            parse("-----BEGIN PGP MESSAGE-----\n\nSGVsbG8=\n-----END PGP KEY-----\n"),
+            Err(ArmorError::LabelMismatch { offset: 38 })
+        );
+        assert_eq!(
+            parse("-----BEGIN PGP MESSAGE-----\n\nSGVsbG8=\n=EHJM\nSGVs\n"),
+            Err(ArmorError::Decode(DecodeError::InvalidByte {
+                offset: 38,
+                byte: b'='
+            }))
+        );
+    }
+}
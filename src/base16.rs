@@ -0,0 +1,351 @@
+//! Base16 (hex) encoding following
+//! [RFC 4648 §8](https://datatracker.ietf.org/doc/html/rfc4648#section-8).
+//!
+//! Every byte is encoded as two hexadecimal digits, optionally separated by a
+//! separator such as a space or colon (`de:ad:be:ef`). Decoding reports the
+//! same [`DecodeError`] as base64.
+
+use crate::decode::{DecodeError, DecodeSliceError};
+use crate::encode::EncodeSliceError;
+
+const UPPER_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+const LOWER_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// The letter case of the digits `A` to `F`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+    /// `0-9A-F`, as specified by RFC 4648.
+    Upper,
+    /// `0-9a-f`, as printed by most hashing tools.
+    Lower,
+}
+
+/// A base16 codec configuration: the case of encoded digits, whether decoding
+/// accepts the other case, and an optional separator between bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Base16 {
+    case: Case,
+    case_sensitive: bool,
+    separator: Option<u8>,
+}
+
+impl Base16 {
+    /// Upper case digits without separators. Decoding accepts either case.
+    pub const UPPER: Base16 = Base16::new(Case::Upper);
+
+    /// Lower case digits without separators. Decoding accepts either case.
+    pub const LOWER: Base16 = Base16::new(Case::Lower);
+
+    /// Create a codec encoding digits in the given case, without separators.
+    /// Decoding accepts either case.
+    pub const fn new(case: Case) -> Self {
+        Self {
+            case,
+            case_sensitive: false,
+            separator: None,
+        }
+    }
+
+    /// Set the case of encoded digits.
+    pub const fn with_case(self, case: Case) -> Self {
+        Self { case, ..self }
+    }
+
+    /// Set whether decoding rejects digits that are not in the codec's case.
+    pub const fn with_case_sensitive(self, case_sensitive: bool) -> Self {
+        Self {
+            case_sensitive,
+            ..self
+        }
+    }
+
+    /// Set a separator written between encoded bytes, e.g. `b':'` or `b' '`.
+    /// Decoding skips the separator between bytes, but not inside one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the separator is not ASCII.
+    pub const fn with_separator(self, separator: Option<u8>) -> Self {
+        if let Some(separator) = separator {
+            assert!(separator.is_ascii(), "separator must be ASCII");
+        }
+        Self { separator, ..self }
+    }
+
+    /// The case of encoded digits.
+    pub const fn case(&self) -> Case {
+        self.case
+    }
+
+    /// Whether decoding rejects digits in the other case.
+    pub const fn case_sensitive(&self) -> bool {
+        self.case_sensitive
+    }
+
+    /// The separator between encoded bytes, if any.
+    pub const fn separator(&self) -> Option<u8> {
+        self.separator
+    }
+
+    /// Calculate the length of the output of encoding `input_len` bytes with
+    /// this codec. Returns `None` if the length does not fit in a `usize`.
+    pub const fn encoded_len(&self, input_len: usize) -> Option<usize> {
+        encoded_len(input_len, self.separator.is_some())
+    }
+
+    /// Encode input bytes into a hex string.
+    pub fn encode(&self, input: &[u8]) -> String {
+        let mut output = String::new();
+        self.encode_to_string(input, &mut output);
+        output
+    }
+
+    /// Encode input bytes and append the hex output to `output`.
+    pub fn encode_to_string(&self, input: &[u8], output: &mut String) {
+        let output_len = self
+            .encoded_len(input.len())
+            .expect("usize overflow when calculating encoded length");
+        let start = output.len();
+        // SAFETY: The appended bytes are overwritten with hex digits and the
+        // separator, which is checked to be ASCII, so the string stays valid UTF-8.
+        let output_bytes = unsafe { output.as_mut_vec() };
+        output_bytes.resize(start + output_len, 0);
+        self.encode_into(input, &mut output_bytes[start..]);
+    }
+
+    /// Encode input bytes into a caller-provided buffer.
+    /// Returns the number of bytes written.
+    pub fn encode_slice(&self, input: &[u8], output: &mut [u8]) -> Result<usize, EncodeSliceError> {
+        match self.encoded_len(input.len()) {
+            Some(output_len) if output_len <= output.len() => {}
+            _ => return Err(EncodeSliceError::OutputSliceTooSmall),
+        }
+        Ok(self.encode_into(input, output))
+    }
+
+    /// Decode hex input into the original bytes.
+    pub fn decode(&self, input: &[u8]) -> Result<Vec<u8>, DecodeError> {
+        let mut output = Vec::new();
+        self.decode_to_vec(input, &mut output)?;
+        Ok(output)
+    }
+
+    /// Decode hex input and append the original bytes to `output`.
+    /// Returns the number of bytes appended. Nothing is appended if decoding fails.
+    pub fn decode_to_vec(&self, input: &[u8], output: &mut Vec<u8>) -> Result<usize, DecodeError> {
+        let start = output.len();
+        self.decode_with(input, |byte| output.push(byte))
+            .inspect_err(|_| output.truncate(start))
+    }
+
+    /// Decode hex input into a caller-provided buffer.
+    /// Returns the number of bytes written.
+    /// The buffer is left in an unspecified state if decoding fails.
+    pub fn decode_slice(&self, input: &[u8], output: &mut [u8]) -> Result<usize, DecodeSliceError> {
+        let digits = input
+            .iter()
+            .filter(|&&byte| Some(byte) != self.separator)
+            .count();
+        if output.len() < digits / 2 {
+            return Err(DecodeSliceError::OutputSliceTooSmall);
+        }
+        let mut written = 0;
+        self.decode_with(input, |byte| {
+            output[written] = byte;
+            written += 1;
+        })?;
+        Ok(written)
+    }
+
+    /// Encode input bytes into the front of `output` and return the number of
+    /// bytes written. The caller must make sure `output` is long enough.
+    fn encode_into(&self, input: &[u8], output: &mut [u8]) -> usize {
+        let digits = match self.case {
+            Case::Upper => UPPER_DIGITS,
+            Case::Lower => LOWER_DIGITS,
+        };
+        let mut written = 0;
+        for (i, &byte) in input.iter().enumerate() {
+            if let Some(separator) = self.separator
+                && i > 0
+            {
+                output[written] = separator;
+                written += 1;
+            }
+            output[written] = digits[(byte >> 4) as usize];
+            output[written + 1] = digits[(byte & 0x0F) as usize];
+            written += 2;
+        }
+        written
+    }
+
+    /// Decode hex input, passing each decoded byte to `emit`.
+    /// Returns the number of decoded bytes.
+    fn decode_with(&self, input: &[u8], mut emit: impl FnMut(u8)) -> Result<usize, DecodeError> {
+        let accepts_upper = !self.case_sensitive || self.case == Case::Upper;
+        let accepts_lower = !self.case_sensitive || self.case == Case::Lower;
+
+        let mut decoded_len = 0;
+        // Offset and value of the high digit of a byte whose low digit is pending
+        let mut high: Option<(usize, u8)> = None;
+        for (offset, &byte) in input.iter().enumerate() {
+            if high.is_none() && Some(byte) == self.separator {
+                continue;
+            }
+            let value = match byte {
+                b'0'..=b'9' => byte - b'0',
+                b'A'..=b'F' if accepts_upper => byte - b'A' + 10,
+                b'a'..=b'f' if accepts_lower => byte - b'a' + 10,
+                _ => return Err(DecodeError::InvalidByte { offset, byte }),
+            };
+            match high.take() {
+                None => high = Some((offset, value)),
+                Some((_, high_value)) => {
+                    emit(high_value << 4 | value);
+                    decoded_len += 1;
+                }
+            }
+        }
+
+        // A lone digit cannot form a byte
+        if let Some((offset, _)) = high {
+            return Err(DecodeError::InputLength {
+                offset,
+                remainder: 1,
+            });
+        }
+        Ok(decoded_len)
+    }
+}
+
+impl Default for Base16 {
+    fn default() -> Self {
+        Self::UPPER
+    }
+}
+
+/// Calculate the length of the hex encoding of `input_len` bytes, with or
+/// without a separator between bytes. Returns `None` if the length does not
+/// fit in a `usize`.
+pub const fn encoded_len(input_len: usize, separator: bool) -> Option<usize> {
+    let Some(digits_len) = input_len.checked_mul(2) else {
+        return None;
+    };
+    if separator && input_len > 0 {
+        digits_len.checked_add(input_len - 1)
+    } else {
+        Some(digits_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc_vectors() {
+        let vectors = [
+            ("", ""),
+            ("f", "66"),
+            ("fo", "666F"),
+            ("foo", "666F6F"),
+            ("foob", "666F6F62"),
+            ("fooba", "666F6F6261"),
+            ("foobar", "666F6F626172"),
+        ];
+        for (input, encoded) in vectors {
+            assert_eq!(Base16::UPPER.encode(input.as_bytes()), encoded);
+            assert_eq!(
+                Base16::LOWER.encode(input.as_bytes()),
+                encoded.to_ascii_lowercase()
+            );
+            assert_eq!(
+                Base16::LOWER.decode(encoded.as_bytes()).unwrap(),
+                input.as_bytes()
+            );
+        }
+    }
+
+    #[test]
+    fn test_case_sensitivity() {
+        assert_eq!(Base16::UPPER.decode(b"dEaD").unwrap(), [0xde, 0xad]);
+        assert_eq!(
+            Base16::LOWER.with_case_sensitive(true).decode(b"deAd"),
+            Err(DecodeError::InvalidByte {
+                offset: 2,
+                byte: b'A'
+            })
+        );
+    }
+
+    #[test]
+    fn test_separators() {
+        let colons = Base16::LOWER.with_separator(Some(b':'));
+        assert_eq!(colons.encode(&[0xde, 0xad, 0xbe, 0xef]), "de:ad:be:ef");
+        assert_eq!(colons.encode(&[]), "");
+        assert_eq!(colons.encoded_len(4), Some(11));
+        assert_eq!(
+            colons.decode(b"de:adbe:ef").unwrap(),
+            [0xde, 0xad, 0xbe, 0xef]
+        );
+        assert_eq!(
+            colons.decode(b"d:e"),
+            Err(DecodeError::InvalidByte {
+                offset: 1,
+                byte: b':'
+            })
+        );
+        assert_eq!(
+            Base16::UPPER.decode(b"DE AD"),
+            Err(DecodeError::InvalidByte {
+                offset: 2,
+                byte: b' '
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(
+            Base16::UPPER.decode(b"ABC"),
+            Err(DecodeError::InputLength {
+                offset: 2,
+                remainder: 1
+            })
+        );
+        assert_eq!(
+            Base16::UPPER.decode(b"0G"),
+            Err(DecodeError::InvalidByte {
+                offset: 1,
+                byte: b'G'
+            })
+        );
+    }
+
+    #[test]
+    fn test_buffer_apis() {
+        let mut buf = [0u8; 4];
+        assert_eq!(Base16::UPPER.encode_slice(b"fo", &mut buf), Ok(4));
+        assert_eq!(&buf, b"666F");
+        assert_eq!(
+            Base16::UPPER.encode_slice(b"foo", &mut buf),
+            Err(EncodeSliceError::OutputSliceTooSmall)
+        );
+
+        let spaces = Base16::UPPER.with_separator(Some(b' '));
+        let mut output = [0u8; 2];
+        assert_eq!(spaces.decode_slice(b"66 6F", &mut output), Ok(2));
+        assert_eq!(&output, b"fo");
+        assert_eq!(
+            spaces.decode_slice(b"66 6F 6F", &mut output),
+            Err(DecodeSliceError::OutputSliceTooSmall)
+        );
+
+        let mut output = b"prefix:".to_vec();
+        assert!(Base16::UPPER.decode_to_vec(b"666", &mut output).is_err());
+        assert_eq!(output, b"prefix:");
+        let mut text = String::from("0x");
+        Base16::LOWER.encode_to_string(&[0xff], &mut text);
+        assert_eq!(text, "0xff");
+    }
+}
@@ -0,0 +1,863 @@
+//! Base32 and base32hex encoding following
+//! [RFC 4648 §6–7](https://datatracker.ietf.org/doc/html/rfc4648#section-6).
+//!
+//! Every 5 input bytes are encoded as 8 symbols from a 32-symbol alphabet,
+//! with `=` padding filling out a partial final chunk. Decoding reports the
+//! same [`DecodeError`] as base64 and follows the same [`DecodePaddingMode`].
+//!
+//! The same bit layout is used without padding by
+//! [z-base-32](https://philzimmermann.com/docs/human-oriented-base-32-encoding.txt),
+//! which differs only in its alphabet.
+//! [Crockford's base32](https://www.crockford.com/base32.html) instead encodes
+//! the input as one big-endian number, as ULIDs do, and is provided by
+//! [`Crockford`] together with its hyphens and optional check symbol.
+
+use crate::PAD_CHAR;
+use crate::alphabet::{AlphabetError, INVALID_INDEX, build_decode_table};
+use crate::decode::{
+    DecodeError, DecodeSliceError, check_padding, trim_padding, with_symbols, with_symbols_skipping,
+};
+use crate::encode::EncodeSliceError;
+use crate::engine::DecodePaddingMode;
+
+/// Number of symbols in a base32 alphabet.
+const N: usize = 32;
+
+const MASK_5_BITS: u64 = 0b1_1111;
+
+/// Crockford check symbols for the values 32 to 36, after the 32 alphabet
+/// symbols for the values below.
+const CROCKFORD_CHECK_SYMBOLS: &[u8; 5] = b"*~$=U";
+
+/// Modulus of the Crockford check symbol.
+const CROCKFORD_CHECK_MODULUS: u32 = 37;
+
+/// The 32 symbols used by a base32 encoding, together with a reverse lookup
+/// table for decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Base32Alphabet {
+    symbols: [u8; N],
+    decode_table: [u8; 256],
+}
+
+/// Unwrap an alphabet in a const context, failing compilation on error.
+const fn expect_alphabet(result: Result<Base32Alphabet, AlphabetError>) -> Base32Alphabet {
+    match result {
+        Ok(alphabet) => alphabet,
+        Err(_) => panic!("invalid built-in alphabet"),
+    }
+}
+
+impl Base32Alphabet {
+    /// The standard alphabet from RFC 4648 §6.
+    pub const STANDARD: Base32Alphabet =
+        expect_alphabet(Base32Alphabet::new(b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567"));
+
+    /// The "extended hex" alphabet from RFC 4648 §7, which preserves the sort
+    /// order of the encoded data.
+    pub const HEX: Base32Alphabet =
+        expect_alphabet(Base32Alphabet::new(b"0123456789ABCDEFGHIJKLMNOPQRSTUV"));
+
+    /// Douglas Crockford's alphabet, which leaves out `I`, `L`, `O` and `U`.
+    /// Decoding is case-insensitive and reads `I` and `L` as `1` and `O` as `0`.
+    pub const CROCKFORD: Base32Alphabet =
+        expect_alphabet(Base32Alphabet::new(b"0123456789ABCDEFGHJKMNPQRSTVWXYZ"))
+            .with_lowercase_aliases()
+            .with_alias(b'I', b'1')
+            .with_alias(b'i', b'1')
+            .with_alias(b'L', b'1')
+            .with_alias(b'l', b'1')
+            .with_alias(b'O', b'0')
+            .with_alias(b'o', b'0');
+
+    /// The z-base-32 alphabet, ordered so that the most common symbols are the
+    /// easiest to read and type.
+    pub const Z_BASE32: Base32Alphabet =
+        expect_alphabet(Base32Alphabet::new(b"ybndrfg8ejkmcpqxot1uwisza345h769"));
+
+    /// Build an alphabet from 32 unique printable ASCII symbols.
+    /// The symbol at position `i` encodes the 5-bit value `i`.
+    pub const fn new(symbols: &[u8]) -> Result<Self, AlphabetError> {
+        let decode_table = match build_decode_table(symbols, N, &[PAD_CHAR]) {
+            Ok(decode_table) => decode_table,
+            Err(err) => return Err(err),
+        };
+        let mut table = [0u8; N];
+        table.copy_from_slice(symbols);
+        Ok(Self {
+            symbols: table,
+            decode_table,
+        })
+    }
+
+    /// The symbols of this alphabet, ordered by the 5-bit value they encode.
+    pub const fn symbols(&self) -> &[u8; N] {
+        &self.symbols
+    }
+
+    /// Get the index of a base32 character in this alphabet, in the range
+    /// `[0, 31]`, or `None` if the character is not in the alphabet.
+    /// Aliases decode to the index of the symbol they stand for.
+    pub const fn index_of(&self, input_char: u8) -> Option<u8> {
+        match self.decode_table[input_char as usize] {
+            INVALID_INDEX => None,
+            index => Some(index),
+        }
+    }
+
+    /// Decode `alias` as the same value as `symbol`. Only used to build the
+    /// built-in alphabets, so conflicts fail compilation.
+    const fn with_alias(mut self, alias: u8, symbol: u8) -> Self {
+        let index = self.decode_table[symbol as usize];
+        assert!(index != INVALID_INDEX, "alias for a missing symbol");
+        assert!(
+            self.decode_table[alias as usize] == INVALID_INDEX,
+            "alias shadows a symbol"
+        );
+        self.decode_table[alias as usize] = index;
+        self
+    }
+
+    /// Decode the lower case form of every upper case symbol as the symbol.
+    const fn with_lowercase_aliases(mut self) -> Self {
+        let mut i = 0;
+        while i < N {
+            let symbol = self.symbols[i];
+            if symbol.is_ascii_uppercase() {
+                self = self.with_alias(symbol.to_ascii_lowercase(), symbol);
+            }
+            i += 1;
+        }
+        self
+    }
+}
+
+impl Default for Base32Alphabet {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+/// A base32 codec configuration, mirroring [`Engine`] for base64: which
+/// alphabet to use and how padding is produced and validated.
+///
+/// [`Engine`]: crate::Engine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Base32 {
+    alphabet: Base32Alphabet,
+    encode_padding: bool,
+    decode_padding_mode: DecodePaddingMode,
+    canonical: bool,
+    ignore_whitespace: bool,
+}
+
+impl Base32 {
+    /// Base32 with the standard alphabet from RFC 4648 §6.
+    pub const STANDARD: Base32 = Base32::new(Base32Alphabet::STANDARD);
+
+    /// Base32 with the standard alphabet without padding, as used by TOTP
+    /// secrets.
+    pub const STANDARD_NO_PAD: Base32 = Base32::STANDARD
+        .with_encode_padding(false)
+        .with_decode_padding_mode(DecodePaddingMode::Forbidden);
+
+    /// Base32 with the extended hex alphabet from RFC 4648 §7.
+    pub const HEX: Base32 = Base32::new(Base32Alphabet::HEX);
+
+    /// Base32 with the extended hex alphabet without padding, as used by
+    /// DNSSEC NSEC3 hashes.
+    pub const HEX_NO_PAD: Base32 = Base32::HEX
+        .with_encode_padding(false)
+        .with_decode_padding_mode(DecodePaddingMode::Forbidden);
+
+    /// z-base-32, which has no padding.
+    pub const Z_BASE32: Base32 = Base32::new(Base32Alphabet::Z_BASE32)
+        .with_encode_padding(false)
+        .with_decode_padding_mode(DecodePaddingMode::Forbidden);
+
+    /// Create a codec for the given alphabet.
+    /// Encoding emits padding and decoding requires it.
+    pub const fn new(alphabet: Base32Alphabet) -> Self {
+        Self {
+            alphabet,
+            encode_padding: true,
+            decode_padding_mode: DecodePaddingMode::Required,
+            canonical: false,
+            ignore_whitespace: false,
+        }
+    }
+
+    /// Set whether encoding appends `=` padding to a partial final chunk.
+    pub const fn with_encode_padding(self, encode_padding: bool) -> Self {
+        Self {
+            encode_padding,
+            ..self
+        }
+    }
+
+    /// Set how decoding validates trailing `=` padding.
+    pub const fn with_decode_padding_mode(self, decode_padding_mode: DecodePaddingMode) -> Self {
+        Self {
+            decode_padding_mode,
+            ..self
+        }
+    }
+
+    /// Set whether decoding rejects a final symbol whose unused low bits are
+    /// not zero.
+    pub const fn with_canonical(self, canonical: bool) -> Self {
+        Self { canonical, ..self }
+    }
+
+    /// Set whether decoding skips ASCII whitespace anywhere in the input.
+    pub const fn with_ignore_whitespace(self, ignore_whitespace: bool) -> Self {
+        Self {
+            ignore_whitespace,
+            ..self
+        }
+    }
+
+    /// The alphabet used by this codec.
+    pub const fn alphabet(&self) -> &Base32Alphabet {
+        &self.alphabet
+    }
+
+    /// Whether encoding appends `=` padding.
+    pub const fn encode_padding(&self) -> bool {
+        self.encode_padding
+    }
+
+    /// How decoding validates trailing `=` padding.
+    pub const fn decode_padding_mode(&self) -> DecodePaddingMode {
+        self.decode_padding_mode
+    }
+
+    /// Whether decoding rejects non-zero trailing bits.
+    pub const fn canonical(&self) -> bool {
+        self.canonical
+    }
+
+    /// Whether decoding skips ASCII whitespace.
+    pub const fn ignore_whitespace(&self) -> bool {
+        self.ignore_whitespace
+    }
+
+    /// Calculate the length of the output of encoding `input_len` bytes with
+    /// this codec. Returns `None` if the length does not fit in a `usize`.
+    pub const fn encoded_len(&self, input_len: usize) -> Option<usize> {
+        encoded_len(input_len, self.encode_padding)
+    }
+
+    /// Encode input bytes into a base32 string.
+    pub fn encode(&self, input: &[u8]) -> String {
+        let mut output = String::new();
+        self.encode_to_string(input, &mut output);
+        output
+    }
+
+    /// Encode input bytes and append the base32 output to `output`.
+    pub fn encode_to_string(&self, input: &[u8], output: &mut String) {
+        let output_len = self
+            .encoded_len(input.len())
+            .expect("usize overflow when calculating encoded length");
+        let start = output.len();
+        // SAFETY: The appended bytes are overwritten with ASCII characters from
+        // the alphabet and padding, so the string stays valid UTF-8.
+        let output_bytes = unsafe { output.as_mut_vec() };
+        output_bytes.resize(start + output_len, 0);
+        self.encode_into(input, &mut output_bytes[start..]);
+    }
+
+    /// Encode input bytes into a caller-provided buffer.
+    /// Returns the number of bytes written.
+    pub fn encode_slice(&self, input: &[u8], output: &mut [u8]) -> Result<usize, EncodeSliceError> {
+        match self.encoded_len(input.len()) {
+            Some(output_len) if output_len <= output.len() => {}
+            _ => return Err(EncodeSliceError::OutputSliceTooSmall),
+        }
+        Ok(self.encode_into(input, output))
+    }
+
+    /// Decode base32 input into the original bytes.
+    pub fn decode(&self, input: &[u8]) -> Result<Vec<u8>, DecodeError> {
+        let mut output = Vec::new();
+        self.decode_to_vec(input, &mut output)?;
+        Ok(output)
+    }
+
+    /// Decode base32 input and append the original bytes to `output`.
+    /// Returns the number of bytes appended. Nothing is appended if decoding fails.
+    pub fn decode_to_vec(&self, input: &[u8], output: &mut Vec<u8>) -> Result<usize, DecodeError> {
+        with_symbols(input, self.ignore_whitespace, |symbols| {
+            let (symbols, output_len) = self.split_padding(symbols)?;
+            let start = output.len();
+            output.resize(start + output_len, 0);
+            self.decode_into(symbols, output_len, &mut output[start..])
+                .inspect_err(|_| output.truncate(start))
+        })
+    }
+
+    /// Decode base32 input into a caller-provided buffer.
+    /// Returns the number of bytes written.
+    /// The buffer is left in an unspecified state if decoding fails.
+    pub fn decode_slice(&self, input: &[u8], output: &mut [u8]) -> Result<usize, DecodeSliceError> {
+        with_symbols(input, self.ignore_whitespace, |symbols| {
+            let (symbols, output_len) = self.split_padding(symbols)?;
+            if output.len() < output_len {
+                return Ok(None);
+            }
+            self.decode_into(symbols, output_len, output).map(Some)
+        })?
+        .ok_or(DecodeSliceError::OutputSliceTooSmall)
+    }
+
+    /// Encode input bytes into the front of `output` and return the number of
+    /// bytes written. The caller must make sure `output` is long enough.
+    fn encode_into(&self, input: &[u8], output: &mut [u8]) -> usize {
+        let (chunks, remainder) = input.as_chunks::<5>();
+        for (i, chunk) in chunks.iter().enumerate() {
+            self.write_symbols(chunk, &mut output[8 * i..8 * i + 8]);
+        }
+
+        let start = 8 * chunks.len();
+        if remainder.is_empty() {
+            return start;
+        }
+        // Each input byte adds 8 bits, so round up to whole 5-bit symbols
+        let written = (remainder.len() * 8).div_ceil(5);
+        let mut chunk = [0u8; 5];
+        chunk[..remainder.len()].copy_from_slice(remainder);
+        self.write_symbols(&chunk, &mut output[start..start + written]);
+
+        if !self.encode_padding {
+            return start + written;
+        }
+        output[start + written..start + 8].fill(PAD_CHAR);
+        start + 8
+    }
+
+    /// Encode a 5-byte chunk into as many symbols as `output` holds, starting
+    /// from the most significant bits.
+    fn write_symbols(&self, chunk: &[u8; 5], output: &mut [u8]) {
+        let value = chunk.iter().fold(0u64, |acc, &byte| acc << 8 | byte as u64);
+        for (i, symbol) in output.iter_mut().enumerate() {
+            *symbol = self.alphabet.symbols[(value >> (35 - 5 * i) & MASK_5_BITS) as usize];
+        }
+    }
+
+    /// Trim and validate the trailing padding of the input.
+    /// Returns the input without padding and the length of the decoded output.
+    fn split_padding<'a>(&self, input: &'a [u8]) -> Result<(&'a [u8], usize), DecodeError> {
+        let (input, trailing_len) = trim_padding(input);
+        let chunks_len = input.len() / 8;
+        let remainder = input.len() % 8;
+        // Only 2, 4, 5 and 7 symbols hold whole bytes without a spare symbol
+        let (output_len, expected_padding) = match remainder {
+            0 => (5 * chunks_len, 0),
+            2 | 4 | 5 | 7 => (5 * chunks_len + remainder * 5 / 8, 8 - remainder),
+            _ => {
+                return Err(DecodeError::InputLength {
+                    offset: 8 * chunks_len,
+                    remainder,
+                });
+            }
+        };
+        check_padding(
+            input.len(),
+            trailing_len,
+            expected_padding,
+            self.decode_padding_mode,
+        )?;
+        Ok((input, output_len))
+    }
+
+    /// Decode base32 symbols whose padding was already validated and trimmed
+    /// by [`Base32::split_padding`], along with the output length it
+    /// calculated, into the front of `output`. Returns the number of bytes
+    /// written. The caller must make sure `output` is long enough.
+    fn decode_into(
+        &self,
+        input: &[u8],
+        output_len: usize,
+        output: &mut [u8],
+    ) -> Result<usize, DecodeError> {
+        // Accumulate the 5-bit values of the symbols at the given offsets
+        let decode_symbols = |start: usize, len: usize| -> Result<u64, DecodeError> {
+            (start..start + len).try_fold(0u64, |acc, offset| {
+                let byte = input[offset];
+                let index = self.alphabet.index_of(byte).ok_or(if byte == PAD_CHAR {
+                    DecodeError::WrongPadding { offset }
+                } else {
+                    DecodeError::InvalidByte { offset, byte }
+                })?;
+                Ok(acc << 5 | index as u64)
+            })
+        };
+
+        let (chunks, remainder) = input.as_chunks::<8>();
+        for i in 0..chunks.len() {
+            let value = decode_symbols(8 * i, 8)?;
+            output[5 * i..5 * i + 5].copy_from_slice(&value.to_be_bytes()[3..]);
+        }
+
+        if !remainder.is_empty() {
+            let start = 8 * chunks.len();
+            let value = decode_symbols(start, remainder.len())?;
+            let bytes_len = remainder.len() * 5 / 8;
+            let unused_bits = remainder.len() * 5 - bytes_len * 8;
+            if self.canonical && value & ((1 << unused_bits) - 1) != 0 {
+                return Err(DecodeError::TrailingBits {
+                    offset: start + remainder.len() - 1,
+                });
+            }
+            let value = value >> unused_bits;
+            let bytes = value.to_be_bytes();
+            output[5 * chunks.len()..output_len].copy_from_slice(&bytes[8 - bytes_len..]);
+        }
+
+        Ok(output_len)
+    }
+}
+
+impl Default for Base32 {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+/// Crockford's base32 for identifiers typed and read by people, such as
+/// license keys. Decoding ignores hyphens, accepts lower case and the
+/// `I`/`L`/`O` aliases, and can verify a trailing mod-37 check symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Crockford {
+    check_symbol: bool,
+    hyphen_group: Option<usize>,
+}
+
+impl Crockford {
+    /// Crockford's base32 without a check symbol or hyphens.
+    pub const STANDARD: Crockford = Crockford {
+        check_symbol: false,
+        hyphen_group: None,
+    };
+
+    /// Crockford's base32 with a trailing check symbol.
+    pub const WITH_CHECK: Crockford = Crockford::STANDARD.with_check_symbol(true);
+
+    /// Set whether encoding appends a check symbol and decoding requires and
+    /// verifies one.
+    pub const fn with_check_symbol(self, check_symbol: bool) -> Self {
+        Self {
+            check_symbol,
+            ..self
+        }
+    }
+
+    /// Set the number of symbols between hyphens written when encoding, e.g.
+    /// `Some(5)` for `ABCDE-FGHIJ`. Decoding skips hyphens either way.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the group length is 0.
+    pub const fn with_hyphen_group(self, hyphen_group: Option<usize>) -> Self {
+        if let Some(len) = hyphen_group {
+            assert!(len > 0, "hyphen group length must be positive");
+        }
+        Self {
+            hyphen_group,
+            ..self
+        }
+    }
+
+    /// Whether encoding appends a check symbol and decoding verifies one.
+    pub const fn check_symbol(&self) -> bool {
+        self.check_symbol
+    }
+
+    /// The number of symbols between hyphens written when encoding, if any.
+    pub const fn hyphen_group(&self) -> Option<usize> {
+        self.hyphen_group
+    }
+
+    /// Encode input bytes into a Crockford base32 string.
+    /// The input is read as one big-endian number and written with the
+    /// fewest symbols that can hold its bits, so the zero bits filling out
+    /// the first symbol come first, e.g. 16 bytes give the 26 symbols of a
+    /// ULID.
+    pub fn encode(&self, input: &[u8]) -> String {
+        let mut symbols = crockford_encode(input);
+        if self.check_symbol {
+            symbols.push(crockford_check_symbol(&symbols));
+        }
+        self.join_groups(&symbols)
+    }
+
+    /// Decode Crockford base32 input into the original bytes.
+    pub fn decode(&self, input: &[u8]) -> Result<Vec<u8>, DecodeError> {
+        let mut output = Vec::new();
+        self.decode_to_vec(input, &mut output)?;
+        Ok(output)
+    }
+
+    /// Decode Crockford base32 input and append the original bytes to
+    /// `output`. Returns the number of bytes appended. Nothing is appended if
+    /// decoding fails.
+    pub fn decode_to_vec(&self, input: &[u8], output: &mut Vec<u8>) -> Result<usize, DecodeError> {
+        with_symbols_skipping(
+            input,
+            |&byte| byte == b'-',
+            |symbols| {
+                let body = self.split_check_symbol(symbols)?;
+                let start = output.len();
+                let decoded_len = crockford_decode_to_vec(body, output)?;
+                if self.check_symbol
+                    && crockford_check_symbol(body) != normalize_symbol(symbols[body.len()])
+                {
+                    output.truncate(start);
+                    return Err(DecodeError::InvalidChecksum { offset: body.len() });
+                }
+                Ok(decoded_len)
+            },
+        )
+    }
+
+    /// Validate the input and rewrite it in canonical form: upper case,
+    /// aliases replaced by the symbols they stand for, and hyphens placed
+    /// according to [`Crockford::hyphen_group`].
+    pub fn normalize(&self, input: &[u8]) -> Result<String, DecodeError> {
+        self.decode(input)?;
+        let symbols: Vec<u8> = input
+            .iter()
+            .filter(|&&byte| byte != b'-')
+            .map(|&byte| normalize_symbol(byte))
+            .collect();
+        Ok(self.join_groups(&symbols))
+    }
+
+    /// Split the check symbol off the end of the input if the codec uses one.
+    fn split_check_symbol<'a>(&self, symbols: &'a [u8]) -> Result<&'a [u8], DecodeError> {
+        if !self.check_symbol {
+            return Ok(symbols);
+        }
+        let Some((&check, body)) = symbols.split_last() else {
+            return Err(DecodeError::InvalidChecksum { offset: 0 });
+        };
+        let check = normalize_symbol(check);
+        if Base32Alphabet::CROCKFORD.index_of(check).is_none()
+            && !CROCKFORD_CHECK_SYMBOLS.contains(&check)
+        {
+            return Err(DecodeError::InvalidByte {
+                offset: body.len(),
+                byte: symbols[body.len()],
+            });
+        }
+        Ok(body)
+    }
+
+    /// Join symbols into a string, with hyphens between groups if configured.
+    fn join_groups(&self, symbols: &[u8]) -> String {
+        let groups: Vec<&[u8]> = match self.hyphen_group {
+            Some(len) => symbols.chunks(len).collect(),
+            None => vec![symbols],
+        };
+        // Symbols are ASCII, as they come from the alphabet or were validated
+        String::from_utf8(groups.join(&b'-')).expect("symbols are ASCII")
+    }
+}
+
+/// Encode input bytes as a big-endian number in `ceil(8 * n / 5)` Crockford
+/// symbols, with the unused high bits of the first symbol set to zero.
+fn crockford_encode(input: &[u8]) -> Vec<u8> {
+    let symbols_len = (input.len() * 8).div_ceil(5);
+    let mut symbols = Vec::with_capacity(symbols_len);
+    // Start with the zero bits that fill out the first symbol
+    let mut bits = symbols_len * 5 - input.len() * 8;
+    let mut value: u64 = 0;
+    for &byte in input {
+        value = value << 8 | byte as u64;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            let index = (value >> bits) & MASK_5_BITS;
+            symbols.push(Base32Alphabet::CROCKFORD.symbols[index as usize]);
+        }
+        value &= (1 << bits) - 1;
+    }
+    symbols
+}
+
+/// Decode Crockford symbols as a big-endian number into `floor(5 * n / 8)`
+/// bytes appended to `output`. Nothing is appended if decoding fails.
+/// The high bits of the first symbols that do not fit in those bytes must be
+/// zero.
+fn crockford_decode_to_vec(input: &[u8], output: &mut Vec<u8>) -> Result<usize, DecodeError> {
+    // Check every symbol before the range, which reports a typo rather than
+    // an overflow caused by it
+    let digits = input
+        .iter()
+        .enumerate()
+        .map(|(offset, &byte)| {
+            Base32Alphabet::CROCKFORD
+                .index_of(byte)
+                .ok_or(DecodeError::InvalidByte { offset, byte })
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+
+    let start = output.len();
+    let output_len = input.len() * 5 / 8;
+    output.reserve(output_len);
+    let mut unused_bits = input.len() * 5 - output_len * 8;
+    let mut bits = 0;
+    let mut value: u64 = 0;
+    for (offset, &digit) in digits.iter().enumerate() {
+        value = value << 5 | digit as u64;
+        bits += 5;
+        if unused_bits > 0 && bits >= unused_bits {
+            // The number does not fit in the output
+            if value >> (bits - unused_bits) != 0 {
+                output.truncate(start);
+                return Err(DecodeError::OutOfRange { offset });
+            }
+            bits -= unused_bits;
+            unused_bits = 0;
+        }
+        while bits >= 8 {
+            bits -= 8;
+            output.push((value >> bits) as u8);
+        }
+        value &= (1 << bits) - 1;
+    }
+    Ok(output_len)
+}
+
+/// Map a valid Crockford symbol, alias or check symbol to its canonical form.
+fn normalize_symbol(byte: u8) -> u8 {
+    match Base32Alphabet::CROCKFORD.index_of(byte) {
+        Some(index) => Base32Alphabet::CROCKFORD.symbols[index as usize],
+        None => byte.to_ascii_uppercase(),
+    }
+}
+
+/// The check symbol of validated Crockford symbols: the big-endian number
+/// they spell modulo 37.
+fn crockford_check_symbol(symbols: &[u8]) -> u8 {
+    let value = symbols.iter().fold(0, |acc, &byte| {
+        let digit = Base32Alphabet::CROCKFORD.index_of(byte).unwrap_or(0) as u32;
+        (acc * N as u32 + digit) % CROCKFORD_CHECK_MODULUS
+    });
+    match Base32Alphabet::CROCKFORD.symbols.get(value as usize) {
+        Some(&symbol) => symbol,
+        None => CROCKFORD_CHECK_SYMBOLS[value as usize - N],
+    }
+}
+
+/// Calculate the length of the base32 encoding of `input_len` bytes, with or
+/// without padding. Returns `None` if the length does not fit in a `usize`.
+pub const fn encoded_len(input_len: usize, padding: bool) -> Option<usize> {
+    let Some(chunks_output_len) = (input_len / 5).checked_mul(8) else {
+        return None;
+    };
+    let remainder_output_len = match input_len % 5 {
+        0 => 0,
+        _ if padding => 8,
+        n => (n * 8).div_ceil(5),
+    };
+    chunks_output_len.checked_add(remainder_output_len)
+}
+
+/// Calculate an upper bound on the decoded length of `encoded_len` base32
+/// symbols, whether padded or not.
+pub const fn decoded_len_estimate(encoded_len: usize) -> usize {
+    encoded_len.div_ceil(8) * 5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test vectors from RFC 4648 §10.
+    const VECTORS: [(&str, &str, &str); 7] = [
+        ("", "", ""),
+        ("f", "MY======", "CO======"),
+        ("fo", "MZXQ====", "CPNG===="),
+        ("foo", "MZXW6===", "CPNMU==="),
+        ("foob", "MZXW6YQ=", "CPNMUOG="),
+        ("fooba", "MZXW6YTB", "CPNMUOJ1"),
+        ("foobar", "MZXW6YTBOI======", "CPNMUOJ1E8======"),
+    ];
+
+    #[test]
+    fn test_rfc_vectors() {
+        for (input, standard, hex) in VECTORS {
+            assert_eq!(Base32::STANDARD.encode(input.as_bytes()), standard);
+            assert_eq!(Base32::HEX.encode(input.as_bytes()), hex);
+            assert_eq!(
+                Base32::STANDARD.decode(standard.as_bytes()).unwrap(),
+                input.as_bytes()
+            );
+            assert_eq!(
+                Base32::HEX.decode(hex.as_bytes()).unwrap(),
+                input.as_bytes()
+            );
+
+            let unpadded = standard.trim_end_matches('=');
+            assert_eq!(Base32::STANDARD_NO_PAD.encode(input.as_bytes()), unpadded);
+            assert_eq!(
+                Base32::STANDARD_NO_PAD.decode(unpadded.as_bytes()).unwrap(),
+                input.as_bytes()
+            );
+            assert_eq!(
+                Base32::STANDARD.encoded_len(input.len()),
+                Some(standard.len())
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(
+            Base32::STANDARD.decode(b"MZXW6Y=="),
+            Err(DecodeError::InputLength {
+                offset: 0,
+                remainder: 6
+            })
+        );
+        assert_eq!(
+            Base32::STANDARD.decode(b"MZXQ==="),
+            Err(DecodeError::WrongPadding { offset: 7 })
+        );
+        assert_eq!(
+            Base32::STANDARD_NO_PAD.decode(b"MZXQ===="),
+            Err(DecodeError::WrongPadding { offset: 4 })
+        );
+        assert_eq!(
+            Base32::STANDARD.decode(b"MY======MY======"),
+            Err(DecodeError::WrongPadding { offset: 2 })
+        );
+        assert_eq!(
+            Base32::STANDARD.decode(b"mzxw6==="),
+            Err(DecodeError::InvalidByte {
+                offset: 0,
+                byte: b'm'
+            })
+        );
+
+        // "MZ" leaves two unused bits set in the final symbol
+        assert_eq!(Base32::STANDARD.decode(b"MZ======").unwrap(), b"f");
+        assert_eq!(
+            Base32::STANDARD.with_canonical(true).decode(b"MZ======"),
+            Err(DecodeError::TrailingBits { offset: 1 })
+        );
+    }
+
+    #[test]
+    fn test_crockford() {
+        assert_eq!(Crockford::STANDARD.encode(b"foobar"), "36DXQP4RBJ");
+        assert_eq!(
+            Crockford::STANDARD.decode(b"36DXQP4RBJ").unwrap(),
+            b"foobar"
+        );
+        // Lower case, aliases and hyphens decode the same
+        assert_eq!(
+            Crockford::STANDARD.decode(b"36dx-qp4r-bj").unwrap(),
+            b"foobar"
+        );
+        assert_eq!(Crockford::STANDARD.decode(b"Oo").unwrap(), b"\0");
+        assert_eq!(
+            Crockford::STANDARD.decode(b"CSQ-U"),
+            Err(DecodeError::InvalidByte {
+                offset: 4,
+                byte: b'U'
+            })
+        );
+
+        // A ULID is a 128-bit number in 26 symbols, with two leading zero bits
+        let ulid = [
+            0x01, 0x56, 0x3E, 0x3A, 0xB5, 0xD3, 0xD6, 0x76, 0x4C, 0x61, 0xEF, 0xB9, 0x93, 0x02,
+            0xBD, 0x5B,
+        ];
+        assert_eq!(
+            Crockford::STANDARD.encode(&ulid),
+            "01ARZ3NDEKTSV4RRFFQ69G5FAV"
+        );
+        assert_eq!(
+            Crockford::STANDARD
+                .decode(b"01arz3ndektsv4rrffq69g5fav")
+                .unwrap(),
+            ulid
+        );
+        assert_eq!(
+            Crockford::STANDARD.decode(b"81ARZ3NDEKTSV4RRFFQ69G5FAV"),
+            Err(DecodeError::OutOfRange { offset: 0 })
+        );
+
+        // 1234 is "16J" with check symbol "D" in Crockford's specification
+        assert_eq!(Crockford::WITH_CHECK.encode(&[0x04, 0xD2]), "016JD");
+        assert_eq!(
+            Crockford::WITH_CHECK.decode(b"016JD").unwrap(),
+            [0x04, 0xD2]
+        );
+
+        let keys = Crockford::WITH_CHECK.with_hyphen_group(Some(4));
+        assert_eq!(keys.encode(b"foobar"), "36DX-QP4R-BJ6");
+        assert_eq!(keys.encode(b""), "0");
+        assert_eq!(keys.decode(b"36dx-qp4r-bj6").unwrap(), b"foobar");
+        assert_eq!(keys.normalize(b"36dxqp4rbj6").unwrap(), "36DX-QP4R-BJ6");
+        assert_eq!(
+            keys.decode(b"36DX-QP4R-BJ7"),
+            Err(DecodeError::InvalidChecksum { offset: 12 })
+        );
+        assert_eq!(
+            keys.decode(b"36DX-QP4R-BJ!"),
+            Err(DecodeError::InvalidByte {
+                offset: 12,
+                byte: b'!'
+            })
+        );
+        assert_eq!(
+            keys.decode(b""),
+            Err(DecodeError::InvalidChecksum { offset: 0 })
+        );
+    }
+
+    #[test]
+    fn test_z_base32() {
+        assert_eq!(Base32::Z_BASE32.encode(b"foobar"), "c3zs6aubqe");
+        assert_eq!(Base32::Z_BASE32.decode(b"jb1sa5dx").unwrap(), b"Hello");
+        assert_eq!(
+            Base32::Z_BASE32.decode(b"JB1SA5DX"),
+            Err(DecodeError::InvalidByte {
+                offset: 0,
+                byte: b'J'
+            })
+        );
+    }
+
+    #[test]
+    fn test_buffer_apis() {
+        let mut buf = [0u8; 8];
+        assert_eq!(Base32::STANDARD.encode_slice(b"foob", &mut buf), Ok(8));
+        assert_eq!(&buf, b"MZXW6YQ=");
+        assert_eq!(
+            Base32::STANDARD.encode_slice(b"foobar", &mut buf),
+            Err(EncodeSliceError::OutputSliceTooSmall)
+        );
+
+        let mut output = [0u8; 4];
+        assert_eq!(
+            Base32::STANDARD.decode_slice(b"MZXW6YQ=", &mut output),
+            Ok(4)
+        );
+        assert_eq!(&output, b"foob");
+        assert_eq!(
+            Base32::STANDARD.decode_slice(b"MZXW6YTB", &mut output),
+            Err(DecodeSliceError::OutputSliceTooSmall)
+        );
+
+        let mut output = b"prefix:".to_vec();
+        let engine = Base32::STANDARD_NO_PAD.with_ignore_whitespace(true);
+        assert_eq!(engine.decode_to_vec(b"JBSW Y3DP", &mut output), Ok(5));
+        assert_eq!(output, b"prefix:Hello");
+        assert_eq!(decoded_len_estimate(8), 5);
+    }
+}
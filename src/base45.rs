@@ -0,0 +1,250 @@
+//! Base45 encoding following
+//! [RFC 9285](https://datatracker.ietf.org/doc/html/rfc9285), as used for
+//! EU digital COVID certificates and other QR code payloads.
+//!
+//! Every 2 input bytes are read as a number `n = a * 256 + b` and encoded as
+//! 3 symbols `c`, `d`, `e` with `n = c + d * 45 + e * 45²`, least significant
+//! first. A final single byte is encoded as 2 symbols. The alphabet is the
+//! QR code alphanumeric set, so the output fits QR alphanumeric mode.
+
+use crate::decode::{DecodeError, DecodeSliceError};
+use crate::encode::EncodeSliceError;
+
+/// Number of symbols in the base45 alphabet.
+const N: u32 = 45;
+
+/// The QR code alphanumeric characters, ordered by the digit they encode.
+const SYMBOLS: &[u8; N as usize] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+/// A base45 codec. RFC 9285 has a single alphabet and no options, so this is
+/// a unit type kept for symmetry with the other codecs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Base45;
+
+impl Base45 {
+    /// Base45 as specified by RFC 9285.
+    pub const STANDARD: Base45 = Base45;
+
+    /// Calculate the length of the output of encoding `input_len` bytes.
+    /// Returns `None` if the length does not fit in a `usize`.
+    pub const fn encoded_len(&self, input_len: usize) -> Option<usize> {
+        encoded_len(input_len)
+    }
+
+    /// Encode input bytes into a base45 string.
+    pub fn encode(&self, input: &[u8]) -> String {
+        let mut output = String::new();
+        self.encode_to_string(input, &mut output);
+        output
+    }
+
+    /// Encode input bytes and append the base45 output to `output`.
+    pub fn encode_to_string(&self, input: &[u8], output: &mut String) {
+        let output_len =
+            encoded_len(input.len()).expect("usize overflow when calculating encoded length");
+        let start = output.len();
+        // SAFETY: The appended bytes are overwritten with symbols from the
+        // ASCII alphabet, so the string stays valid UTF-8.
+        let output_bytes = unsafe { output.as_mut_vec() };
+        output_bytes.resize(start + output_len, 0);
+        encode_into(input, &mut output_bytes[start..]);
+    }
+
+    /// Encode input bytes into a caller-provided buffer.
+    /// Returns the number of bytes written.
+    pub fn encode_slice(&self, input: &[u8], output: &mut [u8]) -> Result<usize, EncodeSliceError> {
+        match encoded_len(input.len()) {
+            Some(output_len) if output_len <= output.len() => {}
+            _ => return Err(EncodeSliceError::OutputSliceTooSmall),
+        }
+        Ok(encode_into(input, output))
+    }
+
+    /// Decode base45 input into the original bytes.
+    pub fn decode(&self, input: &[u8]) -> Result<Vec<u8>, DecodeError> {
+        let mut output = Vec::new();
+        self.decode_to_vec(input, &mut output)?;
+        Ok(output)
+    }
+
+    /// Decode base45 input and append the original bytes to `output`.
+    /// Returns the number of bytes appended. Nothing is appended if decoding fails.
+    pub fn decode_to_vec(&self, input: &[u8], output: &mut Vec<u8>) -> Result<usize, DecodeError> {
+        let start = output.len();
+        output.reserve(decoded_len_estimate(input.len()));
+        decode_with(input, |bytes| output.extend_from_slice(bytes))
+            .inspect_err(|_| output.truncate(start))
+    }
+
+    /// Decode base45 input into a caller-provided buffer.
+    /// Returns the number of bytes written.
+    /// The buffer is left in an unspecified state if decoding fails.
+    pub fn decode_slice(&self, input: &[u8], output: &mut [u8]) -> Result<usize, DecodeSliceError> {
+        if output.len() < decoded_len_estimate(input.len()) {
+            return Err(DecodeSliceError::OutputSliceTooSmall);
+        }
+        let mut written = 0;
+        decode_with(input, |bytes| {
+            output[written..written + bytes.len()].copy_from_slice(bytes);
+            written += bytes.len();
+        })?;
+        Ok(written)
+    }
+}
+
+/// Calculate the length of the base45 encoding of `input_len` bytes.
+/// Returns `None` if the length does not fit in a `usize`.
+pub const fn encoded_len(input_len: usize) -> Option<usize> {
+    let remainder_len = if input_len % 2 == 1 { 2 } else { 0 };
+    match (input_len / 2).checked_mul(3) {
+        Some(len) => len.checked_add(remainder_len),
+        None => None,
+    }
+}
+
+/// Calculate the decoded length of `encoded_len` symbols, rounding a
+/// truncated final group up.
+pub const fn decoded_len_estimate(encoded_len: usize) -> usize {
+    encoded_len / 3 * 2 + if encoded_len.is_multiple_of(3) { 0 } else { 1 }
+}
+
+/// Encode input bytes into the front of `output` and return the number of
+/// bytes written. The caller must make sure `output` is long enough.
+fn encode_into(input: &[u8], output: &mut [u8]) -> usize {
+    let mut written = 0;
+    for chunk in input.chunks(2) {
+        let (mut value, len) = match *chunk {
+            [a, b] => ((a as u32) << 8 | b as u32, 3),
+            [a] => (a as u32, 2),
+            _ => unreachable!(),
+        };
+        for symbol in &mut output[written..written + len] {
+            *symbol = SYMBOLS[(value % N) as usize];
+            value /= N;
+        }
+        written += len;
+    }
+    written
+}
+
+/// Get the digit of a base45 character, or an error pointing at `offset`.
+fn digit(byte: u8, offset: usize) -> Result<u32, DecodeError> {
+    match byte {
+        b'0'..=b'9' => Ok((byte - b'0') as u32),
+        b'A'..=b'Z' => Ok((byte - b'A') as u32 + 10),
+        _ => match SYMBOLS[36..].iter().position(|&symbol| symbol == byte) {
+            Some(index) => Ok(index as u32 + 36),
+            None => Err(DecodeError::InvalidByte { offset, byte }),
+        },
+    }
+}
+
+/// Decode base45 input, passing the bytes of each group to `emit`.
+/// Returns the number of decoded bytes.
+fn decode_with(input: &[u8], mut emit: impl FnMut(&[u8])) -> Result<usize, DecodeError> {
+    let mut decoded_len = 0;
+    for (index, group) in input.chunks(3).enumerate() {
+        let group_start = index * 3;
+        if group.len() == 1 {
+            return Err(DecodeError::InputLength {
+                offset: group_start,
+                remainder: 1,
+            });
+        }
+
+        let mut value = 0;
+        for (i, &byte) in group.iter().enumerate().rev() {
+            value = value * N + digit(byte, group_start + i)?;
+        }
+
+        // Groups encoding more than their bytes can hold have no valid input
+        let (bytes, len) = match group.len() {
+            3 if value <= 0xFFFF => ((value as u16).to_be_bytes(), 2),
+            2 if value <= 0xFF => ([value as u8, 0], 1),
+            _ => {
+                return Err(DecodeError::OutOfRange {
+                    offset: group_start,
+                });
+            }
+        };
+        emit(&bytes[..len]);
+        decoded_len += len;
+    }
+    Ok(decoded_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc_vectors() {
+        let vectors: [(&[u8], &str); 5] = [
+            (b"", ""),
+            (b"AB", "BB8"),
+            (b"Hello!!", "%69 VD92EX0"),
+            (b"base-45", "UJCLQE7W581"),
+            (b"ietf!", "QED8WEX0"),
+        ];
+        for (input, encoded) in vectors {
+            assert_eq!(Base45::STANDARD.encode(input), encoded);
+            assert_eq!(Base45::STANDARD.decode(encoded.as_bytes()).unwrap(), input);
+        }
+        assert_eq!(Base45::STANDARD.encode(&[0xFF, 0xFF]), "FGW");
+        assert_eq!(Base45::STANDARD.decode(b"FGW").unwrap(), [0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        // Largest triplet is 44 + 44 * 45 + 44 * 45² = 91124 > 65535
+        assert_eq!(
+            Base45::STANDARD.decode(b"BB8GGW"),
+            Err(DecodeError::OutOfRange { offset: 3 })
+        );
+        assert_eq!(
+            Base45::STANDARD.decode(b"BB8::"),
+            Err(DecodeError::OutOfRange { offset: 3 })
+        );
+        assert_eq!(
+            Base45::STANDARD.decode(b"BB8B"),
+            Err(DecodeError::InputLength {
+                offset: 3,
+                remainder: 1
+            })
+        );
+        assert_eq!(
+            Base45::STANDARD.decode(b"BB8bB"),
+            Err(DecodeError::InvalidByte {
+                offset: 3,
+                byte: b'b'
+            })
+        );
+    }
+
+    #[test]
+    fn test_buffer_apis() {
+        let mut buf = [0u8; 3];
+        assert_eq!(Base45::STANDARD.encode_slice(b"AB", &mut buf), Ok(3));
+        assert_eq!(&buf, b"BB8");
+        assert_eq!(
+            Base45::STANDARD.encode_slice(b"ABC", &mut buf),
+            Err(EncodeSliceError::OutputSliceTooSmall)
+        );
+
+        let mut output = [0u8; 2];
+        assert_eq!(Base45::STANDARD.decode_slice(b"BB8", &mut output), Ok(2));
+        assert_eq!(&output, b"AB");
+        assert_eq!(
+            Base45::STANDARD.decode_slice(b"BB8BB", &mut output),
+            Err(DecodeSliceError::OutputSliceTooSmall)
+        );
+
+        let mut output = b"prefix:".to_vec();
+        assert!(
+            Base45::STANDARD
+                .decode_to_vec(b"BB8GGW", &mut output)
+                .is_err()
+        );
+        assert_eq!(output, b"prefix:");
+    }
+}
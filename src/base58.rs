@@ -0,0 +1,308 @@
+//! Base58 and Base58Check encoding, as used by Bitcoin addresses and IPFS
+//! content identifiers.
+//!
+//! Unlike the RFC 4648 encodings, base58 treats the whole input as one big
+//! number, so there is no padding and no fixed chunk size. Each leading zero
+//! byte is kept as a leading zero symbol (`1` in the Bitcoin alphabet).
+//! Base58Check appends the first 4 bytes of a double SHA-256 of the payload
+//! before encoding, and verifies them when decoding.
+
+use crate::alphabet::{AlphabetError, INVALID_INDEX, build_decode_table};
+use crate::base_n::{decode_number, encode_number};
+use crate::decode::{DecodeError, DecodeSliceError};
+use crate::encode::EncodeSliceError;
+use crate::sha256::sha256;
+
+/// Number of symbols in a base58 alphabet.
+const N: usize = 58;
+
+/// Length of the Base58Check checksum in bytes.
+const CHECKSUM_LEN: usize = 4;
+
+/// The 58 symbols used by a base58 encoding, together with a reverse lookup
+/// table for decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Base58Alphabet {
+    symbols: [u8; N],
+    decode_table: [u8; 256],
+}
+
+/// Unwrap an alphabet in a const context, failing compilation on error.
+const fn expect_alphabet(result: Result<Base58Alphabet, AlphabetError>) -> Base58Alphabet {
+    match result {
+        Ok(alphabet) => alphabet,
+        Err(_) => panic!("invalid built-in alphabet"),
+    }
+}
+
+impl Base58Alphabet {
+    /// The Bitcoin alphabet, which leaves out `0`, `O`, `I` and `l`.
+    pub const BITCOIN: Base58Alphabet = expect_alphabet(Base58Alphabet::new(
+        b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz",
+    ));
+
+    /// The Flickr alphabet, which swaps the case order of the Bitcoin one.
+    pub const FLICKR: Base58Alphabet = expect_alphabet(Base58Alphabet::new(
+        b"123456789abcdefghijkmnopqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ",
+    ));
+
+    /// The Ripple alphabet.
+    pub const RIPPLE: Base58Alphabet = expect_alphabet(Base58Alphabet::new(
+        b"rpshnaf39wBUDNEGHJKLM4PQRST7VWXYZ2bcdeCg65jkm8oFqi1tuvAxyz",
+    ));
+
+    /// Build an alphabet from 58 unique printable ASCII symbols.
+    /// The symbol at position `i` encodes the digit `i`.
+    pub const fn new(symbols: &[u8]) -> Result<Self, AlphabetError> {
+        let decode_table = match build_decode_table(symbols, N, &[]) {
+            Ok(decode_table) => decode_table,
+            Err(err) => return Err(err),
+        };
+        let mut table = [0u8; N];
+        table.copy_from_slice(symbols);
+        Ok(Self {
+            symbols: table,
+            decode_table,
+        })
+    }
+
+    /// The symbols of this alphabet, ordered by the digit they encode.
+    pub const fn symbols(&self) -> &[u8; N] {
+        &self.symbols
+    }
+
+    /// Get the digit of a base58 character in this alphabet, in the range
+    /// `[0, 57]`, or `None` if the character is not in the alphabet.
+    pub const fn index_of(&self, input_char: u8) -> Option<u8> {
+        match self.decode_table[input_char as usize] {
+            INVALID_INDEX => None,
+            index => Some(index),
+        }
+    }
+}
+
+impl Default for Base58Alphabet {
+    fn default() -> Self {
+        Self::BITCOIN
+    }
+}
+
+/// A base58 codec for a given alphabet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Base58 {
+    alphabet: Base58Alphabet,
+}
+
+impl Base58 {
+    /// Base58 with the Bitcoin alphabet.
+    pub const BITCOIN: Base58 = Base58::new(Base58Alphabet::BITCOIN);
+
+    /// Base58 with the Flickr alphabet.
+    pub const FLICKR: Base58 = Base58::new(Base58Alphabet::FLICKR);
+
+    /// Base58 with the Ripple alphabet.
+    pub const RIPPLE: Base58 = Base58::new(Base58Alphabet::RIPPLE);
+
+    /// Create a codec for the given alphabet.
+    pub const fn new(alphabet: Base58Alphabet) -> Self {
+        Self { alphabet }
+    }
+
+    /// The alphabet used by this codec.
+    pub const fn alphabet(&self) -> &Base58Alphabet {
+        &self.alphabet
+    }
+
+    /// Encode input bytes into a base58 string.
+    pub fn encode(&self, input: &[u8]) -> String {
+        let mut output = String::new();
+        self.encode_to_string(input, &mut output);
+        output
+    }
+
+    /// Encode input bytes and append the base58 output to `output`.
+    pub fn encode_to_string(&self, input: &[u8], output: &mut String) {
+        // SAFETY: Only symbols of the alphabet are appended, which are
+        // printable ASCII, so the string stays valid UTF-8.
+        encode_number(input, self.alphabet.symbols(), unsafe {
+            output.as_mut_vec()
+        });
+    }
+
+    /// Encode input bytes into a caller-provided buffer.
+    /// Returns the number of bytes written.
+    pub fn encode_slice(&self, input: &[u8], output: &mut [u8]) -> Result<usize, EncodeSliceError> {
+        // The length depends on the value of the input, so encode it first
+        let mut encoded = Vec::new();
+        encode_number(input, self.alphabet.symbols(), &mut encoded);
+        output
+            .get_mut(..encoded.len())
+            .ok_or(EncodeSliceError::OutputSliceTooSmall)?
+            .copy_from_slice(&encoded);
+        Ok(encoded.len())
+    }
+
+    /// Decode base58 input into the original bytes.
+    pub fn decode(&self, input: &[u8]) -> Result<Vec<u8>, DecodeError> {
+        let mut output = Vec::new();
+        self.decode_to_vec(input, &mut output)?;
+        Ok(output)
+    }
+
+    /// Decode base58 input and append the original bytes to `output`.
+    /// Returns the number of bytes appended. Nothing is appended if decoding fails.
+    pub fn decode_to_vec(&self, input: &[u8], output: &mut Vec<u8>) -> Result<usize, DecodeError> {
+        decode_number(input, N, |byte| self.alphabet.index_of(byte), output)
+    }
+
+    /// Decode base58 input into a caller-provided buffer.
+    /// Returns the number of bytes written.
+    /// The buffer is left unchanged if decoding fails.
+    pub fn decode_slice(&self, input: &[u8], output: &mut [u8]) -> Result<usize, DecodeSliceError> {
+        // The length depends on the value of the input, so decode it first
+        let decoded = self.decode(input)?;
+        output
+            .get_mut(..decoded.len())
+            .ok_or(DecodeSliceError::OutputSliceTooSmall)?
+            .copy_from_slice(&decoded);
+        Ok(decoded.len())
+    }
+
+    /// Encode a payload as Base58Check, appending its 4-byte checksum.
+    /// The payload usually starts with a version byte.
+    pub fn encode_check(&self, payload: &[u8]) -> String {
+        let mut input = Vec::with_capacity(payload.len() + CHECKSUM_LEN);
+        input.extend_from_slice(payload);
+        input.extend_from_slice(&checksum(payload));
+        self.encode(&input)
+    }
+
+    /// Decode Base58Check input and verify its checksum.
+    /// Returns the payload without the checksum.
+    pub fn decode_check(&self, input: &[u8]) -> Result<Vec<u8>, DecodeError> {
+        let mut output = self.decode(input)?;
+        // The checksum is mixed into the whole number, so errors point at the start
+        let Some(payload_len) = output.len().checked_sub(CHECKSUM_LEN) else {
+            return Err(DecodeError::InvalidChecksum { offset: 0 });
+        };
+        if checksum(&output[..payload_len]) != output[payload_len..] {
+            return Err(DecodeError::InvalidChecksum { offset: 0 });
+        }
+        output.truncate(payload_len);
+        Ok(output)
+    }
+}
+
+impl Default for Base58 {
+    fn default() -> Self {
+        Self::BITCOIN
+    }
+}
+
+/// The Base58Check checksum: the first 4 bytes of a double SHA-256.
+fn checksum(payload: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let digest = sha256(&sha256(payload));
+    let mut checksum = [0u8; CHECKSUM_LEN];
+    checksum.copy_from_slice(&digest[..CHECKSUM_LEN]);
+    checksum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Base16;
+
+    #[test]
+    fn test_encode_decode() {
+        let cases: [(&[u8], &str); 6] = [
+            (b"", ""),
+            (b"\0", "1"),
+            (b"\0\0\x01", "112"),
+            (b"Hello World!", "2NEpo7TZRRrLZSi2U"),
+            (
+                b"The quick brown fox jumps over the lazy dog.",
+                "USm3fpXnKG5EUBx2ndxBDMPVciP5hGey2Jh4NDv6gmeo1LkMeiKrLJUUBk6Z",
+            ),
+            (&[0xff; 4], "7YXq9G"),
+        ];
+        for (input, encoded) in cases {
+            assert_eq!(Base58::BITCOIN.encode(input), encoded);
+            assert_eq!(Base58::BITCOIN.decode(encoded.as_bytes()).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn test_alphabets() {
+        let input = b"\0Hello World!";
+        for codec in [Base58::BITCOIN, Base58::FLICKR, Base58::RIPPLE] {
+            let encoded = codec.encode(input);
+            assert_eq!(encoded.as_bytes()[0], codec.alphabet().symbols()[0]);
+            assert_eq!(codec.decode(encoded.as_bytes()).unwrap(), input);
+        }
+        assert!(Base58::RIPPLE.encode(b"\0").starts_with('r'));
+    }
+
+    #[test]
+    fn test_decode_invalid_byte() {
+        assert_eq!(
+            Base58::BITCOIN.decode(b"11O"),
+            Err(DecodeError::InvalidByte {
+                offset: 2,
+                byte: b'O'
+            })
+        );
+    }
+
+    #[test]
+    fn test_buffer_apis() {
+        let mut buf = [0u8; 6];
+        assert_eq!(Base58::BITCOIN.encode_slice(&[0xff; 4], &mut buf), Ok(6));
+        assert_eq!(&buf, b"7YXq9G");
+        assert_eq!(Base58::BITCOIN.encode_slice(b"\0\0\x01", &mut buf), Ok(3));
+        assert_eq!(&buf[..3], b"112");
+        assert_eq!(
+            Base58::BITCOIN.encode_slice(&[0xff; 5], &mut buf),
+            Err(EncodeSliceError::OutputSliceTooSmall)
+        );
+
+        let mut output = [0u8; 4];
+        assert_eq!(Base58::BITCOIN.decode_slice(b"7YXq9G", &mut output), Ok(4));
+        assert_eq!(output, [0xff; 4]);
+        assert_eq!(
+            Base58::BITCOIN.decode_slice(b"11111", &mut output),
+            Err(DecodeSliceError::OutputSliceTooSmall)
+        );
+        assert_eq!(
+            Base58::BITCOIN.decode_slice(b"7YX0", &mut output),
+            Err(DecodeSliceError::DecodeError(DecodeError::InvalidByte {
+                offset: 3,
+                byte: b'0'
+            }))
+        );
+    }
+
+    #[test]
+    fn test_base58check() {
+        // Version 0 followed by a 20-byte public key hash
+        let payload = Base16::LOWER
+            .decode(b"00f54a5851e9372b87810a8e60cdd2e7cfd80b6e31")
+            .unwrap();
+        let address = Base58::BITCOIN.encode_check(&payload);
+        assert_eq!(address, "1PMycacnJaSqwwJqjawXBErnLsZ7RkXUAs");
+        assert_eq!(
+            Base58::BITCOIN.decode_check(address.as_bytes()).unwrap(),
+            payload
+        );
+
+        let mut corrupted = address.into_bytes();
+        corrupted[10] = b'b';
+        assert_eq!(
+            Base58::BITCOIN.decode_check(&corrupted),
+            Err(DecodeError::InvalidChecksum { offset: 0 })
+        );
+        assert_eq!(
+            Base58::BITCOIN.decode_check(b"1"),
+            Err(DecodeError::InvalidChecksum { offset: 0 })
+        );
+    }
+}
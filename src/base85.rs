@@ -0,0 +1,543 @@
+//! Ascii85 and Z85 encoding.
+//!
+//! Both encode every 4 input bytes as 5 symbols from an 85-symbol alphabet.
+//! [Ascii85](https://en.wikipedia.org/wiki/Ascii85), as found in PostScript
+//! and PDF streams, uses the characters `!` to `u`, may abbreviate a group of
+//! four zero bytes as `z`, and is delimited by `<~` and `~>` in Adobe's
+//! variant. A partial final group of `n` bytes is encoded as `n + 1` symbols.
+//! [Z85](https://rfc.zeromq.org/spec/32/), as used for ZeroMQ CURVE keys,
+//! uses an alphabet that is safe in source code and only allows whole groups.
+
+use crate::alphabet::{INVALID_INDEX, build_decode_table};
+use crate::decode::{DecodeError, DecodeSliceError, with_symbols};
+use crate::encode::{EncodeError, EncodeSliceError};
+
+/// Number of symbols in a base85 alphabet.
+const N: usize = 85;
+
+/// Symbol abbreviating a group of four zero bytes in Ascii85.
+const ZERO_GROUP: u8 = b'z';
+
+const START_DELIMITER: &[u8] = b"<~";
+const END_DELIMITER: &[u8] = b"~>";
+
+/// The 85 symbols used by a base85 encoding, together with a reverse lookup
+/// table for decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Alphabet85 {
+    symbols: [u8; N],
+    decode_table: [u8; 256],
+}
+
+impl Alphabet85 {
+    const ASCII85: Alphabet85 = Alphabet85::new(
+        b"!\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstu",
+    );
+
+    const Z85: Alphabet85 = Alphabet85::new(
+        b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#",
+    );
+
+    const fn new(symbols: &[u8]) -> Self {
+        let Ok(decode_table) = build_decode_table(symbols, N, &[]) else {
+            panic!("invalid built-in alphabet");
+        };
+        let mut table = [0u8; N];
+        table.copy_from_slice(symbols);
+        Self {
+            symbols: table,
+            decode_table,
+        }
+    }
+
+    const fn index_of(&self, input_char: u8) -> Option<u8> {
+        match self.decode_table[input_char as usize] {
+            INVALID_INDEX => None,
+            index => Some(index),
+        }
+    }
+}
+
+/// A base85 codec configuration: the alphabet, whether `z` abbreviates zero
+/// groups, whether output is wrapped in `<~ ~>`, and how strict decoding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Base85 {
+    alphabet: Alphabet85,
+    zero_shortcut: bool,
+    delimiters: bool,
+    partial_groups: bool,
+    ignore_whitespace: bool,
+}
+
+impl Base85 {
+    /// Ascii85 with the `z` shortcut, without delimiters.
+    /// Decoding skips whitespace, as line breaks are common in PDF streams.
+    pub const ASCII85: Base85 = Base85 {
+        alphabet: Alphabet85::ASCII85,
+        zero_shortcut: true,
+        delimiters: false,
+        partial_groups: true,
+        ignore_whitespace: true,
+    };
+
+    /// Adobe's Ascii85, which wraps the output in `<~` and `~>`.
+    pub const ADOBE: Base85 = Base85::ASCII85.with_delimiters(true);
+
+    /// ZeroMQ's Z85 from RFC 32. Decoding rejects input that does not consist
+    /// of whole 5-symbol groups.
+    pub const Z85: Base85 = Base85 {
+        alphabet: Alphabet85::Z85,
+        zero_shortcut: false,
+        delimiters: false,
+        partial_groups: false,
+        ignore_whitespace: false,
+    };
+
+    /// Set whether a group of four zero bytes is encoded as `z`, and whether
+    /// decoding accepts it.
+    pub const fn with_zero_shortcut(self, zero_shortcut: bool) -> Self {
+        Self {
+            zero_shortcut,
+            ..self
+        }
+    }
+
+    /// Set whether encoding wraps the output in `<~` and `~>`.
+    /// Decoding accepts input with or without them either way, but input
+    /// opening with `<~` must end with `~>`.
+    pub const fn with_delimiters(self, delimiters: bool) -> Self {
+        Self { delimiters, ..self }
+    }
+
+    /// Set whether a partial final group is allowed. If so, encoding writes
+    /// one for input whose length is not a multiple of 4, and decoding
+    /// accepts a final group of 2 to 4 symbols. If not, as in Z85, encoding
+    /// such input fails with [`EncodeError::InputLength`], and so does
+    /// decoding it.
+    pub const fn with_partial_groups(self, partial_groups: bool) -> Self {
+        Self {
+            partial_groups,
+            ..self
+        }
+    }
+
+    /// Set whether decoding skips ASCII whitespace anywhere in the input.
+    pub const fn with_ignore_whitespace(self, ignore_whitespace: bool) -> Self {
+        Self {
+            ignore_whitespace,
+            ..self
+        }
+    }
+
+    /// Whether `z` abbreviates a group of four zero bytes.
+    pub const fn zero_shortcut(&self) -> bool {
+        self.zero_shortcut
+    }
+
+    /// Whether encoding wraps the output in `<~` and `~>`.
+    pub const fn delimiters(&self) -> bool {
+        self.delimiters
+    }
+
+    /// Whether a partial final group is allowed.
+    pub const fn partial_groups(&self) -> bool {
+        self.partial_groups
+    }
+
+    /// Whether decoding skips ASCII whitespace.
+    pub const fn ignore_whitespace(&self) -> bool {
+        self.ignore_whitespace
+    }
+
+    /// Calculate the maximum length of the output of encoding `input_len`
+    /// bytes with this codec. Zero groups abbreviated as `z` make the actual
+    /// output shorter. Returns `None` if the length does not fit in a `usize`.
+    pub const fn encoded_len(&self, input_len: usize) -> Option<usize> {
+        let Some(groups_len) = (input_len / 4).checked_mul(5) else {
+            return None;
+        };
+        let remainder_len = match input_len % 4 {
+            0 => 0,
+            n => n + 1,
+        };
+        let delimiters_len = if self.delimiters {
+            START_DELIMITER.len() + END_DELIMITER.len()
+        } else {
+            0
+        };
+        match groups_len.checked_add(remainder_len) {
+            Some(len) => len.checked_add(delimiters_len),
+            None => None,
+        }
+    }
+
+    /// Calculate an upper bound on the decoded length of `encoded_len`
+    /// symbols.
+    pub const fn decoded_len_estimate(&self, encoded_len: usize) -> usize {
+        if self.zero_shortcut {
+            // Every symbol may be a `z` standing for four bytes
+            encoded_len.saturating_mul(4)
+        } else {
+            encoded_len.div_ceil(5) * 4
+        }
+    }
+
+    /// Encode input bytes into a base85 string.
+    /// Fails only if partial groups are not allowed and the input length is
+    /// not a multiple of 4.
+    pub fn encode(&self, input: &[u8]) -> Result<String, EncodeError> {
+        let mut output = String::new();
+        self.encode_to_string(input, &mut output)?;
+        Ok(output)
+    }
+
+    /// Encode input bytes and append the base85 output to `output`.
+    /// Nothing is appended if encoding fails.
+    pub fn encode_to_string(&self, input: &[u8], output: &mut String) -> Result<(), EncodeError> {
+        self.check_input_len(input)?;
+        let output_len = self
+            .encoded_len(input.len())
+            .expect("usize overflow when calculating encoded length");
+        let start = output.len();
+        // SAFETY: The appended bytes are overwritten with ASCII characters from
+        // the alphabet and delimiters, and the unused rest is truncated, so the
+        // string stays valid UTF-8.
+        let output_bytes = unsafe { output.as_mut_vec() };
+        output_bytes.resize(start + output_len, 0);
+        let written = self.encode_into(input, &mut output_bytes[start..]);
+        output_bytes.truncate(start + written);
+        Ok(())
+    }
+
+    /// Encode input bytes into a caller-provided buffer, which must hold at
+    /// least [`Base85::encoded_len`] bytes. Returns the number of bytes written.
+    pub fn encode_slice(&self, input: &[u8], output: &mut [u8]) -> Result<usize, EncodeSliceError> {
+        self.check_input_len(input)?;
+        match self.encoded_len(input.len()) {
+            Some(output_len) if output_len <= output.len() => {}
+            _ => return Err(EncodeSliceError::OutputSliceTooSmall),
+        }
+        Ok(self.encode_into(input, output))
+    }
+
+    /// Decode base85 input into the original bytes.
+    pub fn decode(&self, input: &[u8]) -> Result<Vec<u8>, DecodeError> {
+        let mut output = Vec::new();
+        self.decode_to_vec(input, &mut output)?;
+        Ok(output)
+    }
+
+    /// Decode base85 input and append the original bytes to `output`.
+    /// Returns the number of bytes appended. Nothing is appended if decoding fails.
+    pub fn decode_to_vec(&self, input: &[u8], output: &mut Vec<u8>) -> Result<usize, DecodeError> {
+        let start = output.len();
+        self.decode_with(input, |bytes| output.extend_from_slice(bytes))
+            .inspect_err(|_| output.truncate(start))
+    }
+
+    /// Decode base85 input into a caller-provided buffer.
+    /// Returns the number of bytes written.
+    /// The buffer is left in an unspecified state if decoding fails.
+    pub fn decode_slice(&self, input: &[u8], output: &mut [u8]) -> Result<usize, DecodeSliceError> {
+        let mut written = 0;
+        let mut too_small = false;
+        self.decode_with(input, |bytes| {
+            match output.get_mut(written..written + bytes.len()) {
+                Some(dest) => dest.copy_from_slice(bytes),
+                None => too_small = true,
+            }
+            written += bytes.len();
+        })?;
+        if too_small {
+            return Err(DecodeSliceError::OutputSliceTooSmall);
+        }
+        Ok(written)
+    }
+
+    /// Reject input that would need a partial final group when those are not
+    /// allowed, as Z85 has no way to decode one.
+    fn check_input_len(&self, input: &[u8]) -> Result<(), EncodeError> {
+        let remainder = input.len() % 4;
+        if self.partial_groups || remainder == 0 {
+            return Ok(());
+        }
+        Err(EncodeError::InputLength {
+            offset: input.len() - remainder,
+            remainder,
+        })
+    }
+
+    /// Encode input bytes into the front of `output` and return the number of
+    /// bytes written. The caller must make sure `output` holds at least
+    /// [`Base85::encoded_len`] bytes and that the input length is allowed.
+    fn encode_into(&self, input: &[u8], output: &mut [u8]) -> usize {
+        let mut written = 0;
+        if self.delimiters {
+            output[..START_DELIMITER.len()].copy_from_slice(START_DELIMITER);
+            written += START_DELIMITER.len();
+        }
+
+        let (groups, remainder) = input.as_chunks::<4>();
+        for group in groups {
+            let value = u32::from_be_bytes(*group);
+            if self.zero_shortcut && value == 0 {
+                output[written] = ZERO_GROUP;
+                written += 1;
+            } else {
+                self.write_symbols(value, &mut output[written..written + 5]);
+                written += 5;
+            }
+        }
+        if !remainder.is_empty() {
+            // Pad with zero bytes and keep one symbol more than there are bytes
+            let mut group = [0u8; 4];
+            group[..remainder.len()].copy_from_slice(remainder);
+            let len = remainder.len() + 1;
+            self.write_symbols(
+                u32::from_be_bytes(group),
+                &mut output[written..written + len],
+            );
+            written += len;
+        }
+
+        if self.delimiters {
+            output[written..written + END_DELIMITER.len()].copy_from_slice(END_DELIMITER);
+            written += END_DELIMITER.len();
+        }
+        written
+    }
+
+    /// Write the base85 digits of `value`, most significant first, keeping as
+    /// many as `output` holds.
+    fn write_symbols(&self, mut value: u32, output: &mut [u8]) {
+        let mut digits = [0u8; 5];
+        for digit in digits.iter_mut().rev() {
+            *digit = self.alphabet.symbols[(value % N as u32) as usize];
+            value /= N as u32;
+        }
+        output.copy_from_slice(&digits[..output.len()]);
+    }
+
+    /// Decode base85 input, passing the bytes of each group to `emit`.
+    /// Returns the number of decoded bytes.
+    fn decode_with(&self, input: &[u8], mut emit: impl FnMut(&[u8])) -> Result<usize, DecodeError> {
+        // Skip the delimiters if present, keeping offsets into the whole input
+        let (body_start, body) = match input.strip_prefix(START_DELIMITER) {
+            Some(rest) => {
+                let rest = if self.ignore_whitespace {
+                    rest.trim_ascii_end()
+                } else {
+                    rest
+                };
+                let body =
+                    rest.strip_suffix(END_DELIMITER)
+                        .ok_or(DecodeError::MissingDelimiter {
+                            offset: input.len(),
+                        })?;
+                (START_DELIMITER.len(), body)
+            }
+            None => (0, input),
+        };
+
+        with_symbols(body, self.ignore_whitespace, |symbols| {
+            let mut decoded_len = 0;
+            let mut value = 0u64;
+            let mut group_start = 0;
+            let mut group_len = 0;
+            for (offset, &byte) in symbols.iter().enumerate() {
+                if self.zero_shortcut && byte == ZERO_GROUP && group_len == 0 {
+                    emit(&[0; 4]);
+                    decoded_len += 4;
+                    continue;
+                }
+                let digit = self
+                    .alphabet
+                    .index_of(byte)
+                    .ok_or(DecodeError::InvalidByte { offset, byte })?;
+                if group_len == 0 {
+                    group_start = offset;
+                }
+                value = value * N as u64 + digit as u64;
+                group_len += 1;
+                if group_len == 5 {
+                    let bytes = group_bytes(value, group_start)?;
+                    emit(&bytes);
+                    decoded_len += 4;
+                    value = 0;
+                    group_len = 0;
+                }
+            }
+
+            if group_len > 0 {
+                // A single symbol cannot form a byte
+                if group_len == 1 || !self.partial_groups {
+                    return Err(DecodeError::InputLength {
+                        offset: group_start,
+                        remainder: group_len,
+                    });
+                }
+                // Pad with the highest digit so the kept bytes round correctly
+                for _ in group_len..5 {
+                    value = value * N as u64 + (N - 1) as u64;
+                }
+                let bytes = group_bytes(value, group_start)?;
+                emit(&bytes[..group_len - 1]);
+                decoded_len += group_len - 1;
+            }
+            Ok(decoded_len)
+        })
+        .map_err(|err| err.map_offset(|offset| body_start + offset))
+    }
+}
+
+impl Default for Base85 {
+    fn default() -> Self {
+        Self::ASCII85
+    }
+}
+
+/// The 4 bytes of a decoded group, or an error if the group does not fit.
+fn group_bytes(value: u64, group_start: usize) -> Result<[u8; 4], DecodeError> {
+    u32::try_from(value)
+        .map(u32::to_be_bytes)
+        .map_err(|_| DecodeError::OutOfRange {
+            offset: group_start,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii85() {
+        let cases: [(&[u8], &str); 6] = [
+            (b"", ""),
+            (b"\0\0\0\0", "z"),
+            (b"\0\0\0", "!!!!"),
+            (b"Man ", "9jqo^"),
+            (b"sure.", "F*2M7/c"),
+            (b"\0\0\0\0\xff\xff\xff\xff", "zs8W-!"),
+        ];
+        for (input, encoded) in cases {
+            assert_eq!(Base85::ASCII85.encode(input).unwrap(), encoded);
+            assert_eq!(Base85::ASCII85.decode(encoded.as_bytes()).unwrap(), input);
+        }
+        assert_eq!(Base85::ADOBE.encode(b"sure.").unwrap(), "<~F*2M7/c~>");
+        assert_eq!(Base85::ADOBE.decode(b"<~F*2M\n7/c~>").unwrap(), b"sure.");
+        assert_eq!(Base85::ADOBE.decode(b"<~F*2M7/c~>\n").unwrap(), b"sure.");
+        assert_eq!(
+            Base85::ADOBE.decode(b"<~F*2M7/c"),
+            Err(DecodeError::MissingDelimiter { offset: 9 })
+        );
+    }
+
+    #[test]
+    fn test_z85() {
+        // Test vector from RFC 32
+        let input = [0x86, 0x4F, 0xD2, 0x6F, 0xB5, 0x59, 0xF7, 0x5B];
+        assert_eq!(Base85::Z85.encode(&input).unwrap(), "HelloWorld");
+        assert_eq!(Base85::Z85.decode(b"HelloWorld").unwrap(), input);
+        assert_eq!(Base85::Z85.encode(&[0; 4]).unwrap(), "00000");
+        assert_eq!(
+            Base85::Z85
+                .with_partial_groups(true)
+                .encode(&[0x86, 0x4F])
+                .unwrap(),
+            "Hed"
+        );
+        assert_eq!(
+            Base85::Z85.with_partial_groups(true).decode(b"Hed"),
+            Ok(vec![0x86, 0x4F])
+        );
+        assert_eq!(
+            Base85::Z85.decode(b"Hello"),
+            Ok(vec![0x86, 0x4F, 0xD2, 0x6F])
+        );
+        assert_eq!(
+            Base85::Z85.decode(b"HelloWor"),
+            Err(DecodeError::InputLength {
+                offset: 5,
+                remainder: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_z85_encode_partial_group() {
+        // RFC 32 leaves no way to decode a partial group, so the encoder
+        // rejects it rather than writing output that does not round-trip
+        let err = EncodeError::InputLength {
+            offset: 4,
+            remainder: 2,
+        };
+        let input = [0x86, 0x4F, 0xD2, 0x6F, 0xB5, 0x59];
+        assert_eq!(Base85::Z85.encode(&input), Err(err.clone()));
+        let mut output = String::from("x");
+        assert_eq!(
+            Base85::Z85.encode_to_string(&input, &mut output),
+            Err(err.clone())
+        );
+        assert_eq!(output, "x");
+        let mut buf = [0u8; 10];
+        assert_eq!(
+            Base85::Z85.encode_slice(&input, &mut buf),
+            Err(EncodeSliceError::EncodeError(err))
+        );
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(
+            Base85::ASCII85.decode(b"9jqo^v"),
+            Err(DecodeError::InvalidByte {
+                offset: 5,
+                byte: b'v'
+            })
+        );
+        assert_eq!(
+            Base85::ASCII85.decode(b"9jqo^F"),
+            Err(DecodeError::InputLength {
+                offset: 5,
+                remainder: 1
+            })
+        );
+        // `z` inside a group is not a shortcut
+        assert_eq!(
+            Base85::ASCII85.decode(b"9jzo^"),
+            Err(DecodeError::InvalidByte {
+                offset: 2,
+                byte: b'z'
+            })
+        );
+        assert_eq!(
+            Base85::ADOBE.decode(b"<~9jqo^s8W-\"~>"),
+            Err(DecodeError::OutOfRange { offset: 7 })
+        );
+    }
+
+    #[test]
+    fn test_buffer_apis() {
+        let mut buf = [0u8; 9];
+        assert_eq!(Base85::ADOBE.encoded_len(4), Some(9));
+        assert_eq!(Base85::ADOBE.encode_slice(&[0; 4], &mut buf), Ok(5));
+        assert_eq!(&buf[..5], b"<~z~>");
+        assert_eq!(
+            Base85::ADOBE.encode_slice(b"Man s", &mut buf),
+            Err(EncodeSliceError::OutputSliceTooSmall)
+        );
+
+        let mut output = [0u8; 4];
+        assert_eq!(Base85::ASCII85.decode_slice(b"9jqo^", &mut output), Ok(4));
+        assert_eq!(&output, b"Man ");
+        assert_eq!(
+            Base85::ASCII85.decode_slice(b"zz", &mut output),
+            Err(DecodeSliceError::OutputSliceTooSmall)
+        );
+
+        let mut output = b"prefix:".to_vec();
+        assert!(Base85::Z85.decode_to_vec(b"Hello!", &mut output).is_err());
+        assert_eq!(Base85::Z85.decode_to_vec(b"Hello", &mut output), Ok(4));
+        assert_eq!(output, b"prefix:\x86\x4F\xD2\x6F");
+    }
+}
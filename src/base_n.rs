@@ -0,0 +1,321 @@
+//! Arbitrary-radix encoding for alphabets of 2 to 256 symbols, such as the
+//! base36 and base62 used for short links and compact identifiers.
+//!
+//! Like base58, the whole input is treated as one big-endian number and
+//! written in the radix of the alphabet, so the output has no padding and no
+//! fixed chunk size. Each leading zero byte is kept as one leading zero symbol
+//! (the first symbol of the alphabet), which makes the encoding of every input
+//! unique and lets decoding restore the exact input length.
+
+use crate::alphabet::AlphabetError;
+use crate::decode::DecodeError;
+
+/// The symbols used by an arbitrary-radix encoding, together with a reverse
+/// lookup table for decoding. The radix is the number of symbols.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BaseNAlphabet {
+    symbols: [u8; 256],
+    len: usize,
+    decode_table: [Option<u8>; 256],
+}
+
+/// Unwrap an alphabet in a const context, failing compilation on error.
+const fn expect_alphabet(result: Result<BaseNAlphabet, AlphabetError>) -> BaseNAlphabet {
+    match result {
+        Ok(alphabet) => alphabet,
+        Err(_) => panic!("invalid built-in alphabet"),
+    }
+}
+
+impl BaseNAlphabet {
+    /// Digits followed by lower case letters.
+    pub const BASE36: BaseNAlphabet =
+        expect_alphabet(BaseNAlphabet::new(b"0123456789abcdefghijklmnopqrstuvwxyz"));
+
+    /// Digits followed by upper and then lower case letters, in ASCII order.
+    pub const BASE62: BaseNAlphabet = expect_alphabet(BaseNAlphabet::new(
+        b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz",
+    ));
+
+    /// Build an alphabet from 2 to 256 unique symbols.
+    /// The symbol at position `i` encodes the digit `i`. Symbols may be any
+    /// byte, but only ASCII alphabets can be encoded to a [`String`].
+    pub const fn new(symbols: &[u8]) -> Result<Self, AlphabetError> {
+        if symbols.len() < 2 || symbols.len() > 256 {
+            return Err(AlphabetError::InvalidLength(symbols.len()));
+        }
+
+        let mut table = [0u8; 256];
+        let mut decode_table = [None; 256];
+        let mut i = 0;
+        while i < symbols.len() {
+            let symbol = symbols[i];
+            if decode_table[symbol as usize].is_some() {
+                return Err(AlphabetError::DuplicatedByte(symbol));
+            }
+            table[i] = symbol;
+            decode_table[symbol as usize] = Some(i as u8);
+            i += 1;
+        }
+        Ok(Self {
+            symbols: table,
+            len: symbols.len(),
+            decode_table,
+        })
+    }
+
+    /// The symbols of this alphabet, ordered by the digit they encode.
+    pub fn symbols(&self) -> &[u8] {
+        &self.symbols[..self.len]
+    }
+
+    /// The number of symbols, which is the radix of the encoding.
+    pub const fn radix(&self) -> usize {
+        self.len
+    }
+
+    /// Whether every symbol is an ASCII character.
+    pub fn is_ascii(&self) -> bool {
+        self.symbols().is_ascii()
+    }
+
+    /// Get the digit of a character in this alphabet, in the range
+    /// `[0, radix - 1]`, or `None` if the character is not in the alphabet.
+    pub const fn index_of(&self, input_char: u8) -> Option<u8> {
+        self.decode_table[input_char as usize]
+    }
+}
+
+impl Default for BaseNAlphabet {
+    fn default() -> Self {
+        Self::BASE62
+    }
+}
+
+/// An arbitrary-radix codec for a given alphabet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BaseN {
+    alphabet: BaseNAlphabet,
+}
+
+impl BaseN {
+    /// Base36 with digits and lower case letters.
+    pub const BASE36: BaseN = BaseN::new(BaseNAlphabet::BASE36);
+
+    /// Base62 with digits, upper case and lower case letters.
+    pub const BASE62: BaseN = BaseN::new(BaseNAlphabet::BASE62);
+
+    /// Create a codec for the given alphabet.
+    pub const fn new(alphabet: BaseNAlphabet) -> Self {
+        Self { alphabet }
+    }
+
+    /// The alphabet used by this codec.
+    pub const fn alphabet(&self) -> &BaseNAlphabet {
+        &self.alphabet
+    }
+
+    /// Encode input bytes into a string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the alphabet has non-ASCII symbols. Use
+    /// [`BaseN::encode_bytes`] for those.
+    pub fn encode(&self, input: &[u8]) -> String {
+        let mut output = String::new();
+        self.encode_to_string(input, &mut output);
+        output
+    }
+
+    /// Encode input bytes and append the output to `output`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the alphabet has non-ASCII symbols.
+    pub fn encode_to_string(&self, input: &[u8], output: &mut String) {
+        assert!(
+            self.alphabet.is_ascii(),
+            "alphabet must be ASCII to encode to a string"
+        );
+        // SAFETY: Only symbols of the alphabet are appended, which are checked
+        // to be ASCII, so the string stays valid UTF-8.
+        self.encode_to_vec(input, unsafe { output.as_mut_vec() });
+    }
+
+    /// Encode input bytes into a vector of symbols.
+    pub fn encode_bytes(&self, input: &[u8]) -> Vec<u8> {
+        let mut output = Vec::new();
+        self.encode_to_vec(input, &mut output);
+        output
+    }
+
+    /// Encode input bytes and append the symbols to `output`.
+    pub fn encode_to_vec(&self, input: &[u8], output: &mut Vec<u8>) {
+        encode_number(input, self.alphabet.symbols(), output);
+    }
+
+    /// Decode input into the original bytes.
+    pub fn decode(&self, input: &[u8]) -> Result<Vec<u8>, DecodeError> {
+        let mut output = Vec::new();
+        self.decode_to_vec(input, &mut output)?;
+        Ok(output)
+    }
+
+    /// Decode input and append the original bytes to `output`.
+    /// Returns the number of bytes appended. Nothing is appended if decoding fails.
+    pub fn decode_to_vec(&self, input: &[u8], output: &mut Vec<u8>) -> Result<usize, DecodeError> {
+        decode_number(
+            input,
+            self.alphabet.radix(),
+            |byte| self.alphabet.index_of(byte),
+            output,
+        )
+    }
+}
+
+impl Default for BaseN {
+    fn default() -> Self {
+        Self::BASE62
+    }
+}
+
+/// Encode input bytes as one big-endian number in the radix of `symbols` and
+/// append its symbols to `output`, keeping each leading zero byte as a leading
+/// `symbols[0]`. Shared with [`Base58`](crate::Base58).
+pub(crate) fn encode_number(input: &[u8], symbols: &[u8], output: &mut Vec<u8>) {
+    let radix = symbols.len() as u32;
+    let zeros = input.iter().take_while(|&&byte| byte == 0).count();
+
+    // Repeatedly multiply the number so far by 256 and add the next byte,
+    // keeping it as little-endian digits. Each digit holds at least
+    // `ilog2(radix)` bits, which bounds their number.
+    let bits_per_digit = radix.ilog2() as usize;
+    let mut digits: Vec<u8> = Vec::with_capacity((input.len() - zeros) * 8 / bits_per_digit + 1);
+    for &byte in &input[zeros..] {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % radix) as u8;
+            carry /= radix;
+        }
+        while carry > 0 {
+            digits.push((carry % radix) as u8);
+            carry /= radix;
+        }
+    }
+
+    output.reserve(zeros + digits.len());
+    output.extend(std::iter::repeat_n(symbols[0], zeros));
+    output.extend(digits.iter().rev().map(|&digit| symbols[digit as usize]));
+}
+
+/// Decode symbols spelling one big-endian number in `radix`, looked up with
+/// `index_of`, and append its bytes to `output`, keeping each leading zero
+/// symbol as a leading zero byte. Returns the number of bytes appended.
+/// Nothing is appended if decoding fails.
+pub(crate) fn decode_number(
+    input: &[u8],
+    radix: usize,
+    index_of: impl Fn(u8) -> Option<u8>,
+    output: &mut Vec<u8>,
+) -> Result<usize, DecodeError> {
+    let radix = radix as u32;
+    let zeros = input
+        .iter()
+        .take_while(|&&byte| index_of(byte) == Some(0))
+        .count();
+
+    // Repeatedly multiply the number so far by the radix and add the next
+    // digit, keeping it as little-endian bytes
+    let mut bytes: Vec<u8> = Vec::with_capacity(input.len() - zeros);
+    for (offset, &byte) in input.iter().enumerate().skip(zeros) {
+        let digit = index_of(byte).ok_or(DecodeError::InvalidByte { offset, byte })?;
+        let mut carry = digit as u32;
+        for value in bytes.iter_mut() {
+            carry += *value as u32 * radix;
+            *value = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    output.reserve(zeros + bytes.len());
+    output.extend(std::iter::repeat_n(0, zeros));
+    output.extend(bytes.iter().rev());
+    Ok(zeros + bytes.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Base58;
+
+    #[test]
+    fn test_base36_base62() {
+        let cases: [(&[u8], &str, &str); 5] = [
+            (b"", "", ""),
+            (b"\0", "0", "0"),
+            (b"\0\0\x01", "001", "001"),
+            (&[0xff; 4], "1z141z3", "4gfFC3"),
+            (b"Hello World!", "2678lx5gvmsv1dro9b5", "T8dgcjRGkZ3aysdN"),
+        ];
+        for (input, base36, base62) in cases {
+            assert_eq!(BaseN::BASE36.encode(input), base36);
+            assert_eq!(BaseN::BASE62.encode(input), base62);
+            assert_eq!(BaseN::BASE36.decode(base36.as_bytes()).unwrap(), input);
+            assert_eq!(BaseN::BASE62.decode(base62.as_bytes()).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn test_custom_alphabets() {
+        let binary = BaseN::new(BaseNAlphabet::new(b"01").unwrap());
+        assert_eq!(binary.encode(&[0, 5]), "0101");
+        assert_eq!(binary.decode(b"0101").unwrap(), [0, 5]);
+
+        // Matches the dedicated base58 codec
+        let bitcoin = BaseN::new(BaseNAlphabet::new(Base58::BITCOIN.alphabet().symbols()).unwrap());
+        let input = b"\0\0The quick brown fox";
+        assert_eq!(bitcoin.encode(input), Base58::BITCOIN.encode(input));
+
+        // Every byte value as a symbol
+        let symbols: Vec<u8> = (0..=255).collect();
+        let base256 = BaseN::new(BaseNAlphabet::new(&symbols).unwrap());
+        let input = [0, 0, 1, 0xff, 0x80];
+        assert_eq!(base256.encode_bytes(&input), input);
+        assert_eq!(base256.decode(&input).unwrap(), input);
+    }
+
+    #[test]
+    fn test_invalid_alphabets() {
+        assert_eq!(
+            BaseNAlphabet::new(b"0"),
+            Err(AlphabetError::InvalidLength(1))
+        );
+        assert_eq!(
+            BaseNAlphabet::new(&[0; 257]),
+            Err(AlphabetError::InvalidLength(257))
+        );
+        assert_eq!(
+            BaseNAlphabet::new(b"0120"),
+            Err(AlphabetError::DuplicatedByte(b'0'))
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid_byte() {
+        assert_eq!(
+            BaseN::BASE36.decode(b"00aZ"),
+            Err(DecodeError::InvalidByte {
+                offset: 3,
+                byte: b'Z'
+            })
+        );
+        let mut output = b"prefix:".to_vec();
+        assert!(BaseN::BASE62.decode_to_vec(b"a-b", &mut output).is_err());
+        assert_eq!(output, b"prefix:");
+    }
+}
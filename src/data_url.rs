@@ -0,0 +1,385 @@
+//! Data URLs following [RFC 2397](https://datatracker.ietf.org/doc/html/rfc2397).
+//!
+//! A data URL embeds a payload directly in a URL as
+//! `data:[<mediatype>][;base64],<data>`. The payload is base64 if the
+//! `;base64` flag is present and percent-encoded otherwise.
+
+use std::fmt;
+
+use crate::decode::{DecodeError, decode_bytes};
+use crate::engine::{DecodePaddingMode, Engine};
+
+const SCHEME: &str = "data:";
+
+/// Media type assumed when a data URL omits it.
+const DEFAULT_MEDIA_TYPE: &str = "text/plain";
+
+/// Charset assumed when a data URL omits both the media type and parameters.
+const DEFAULT_CHARSET: &str = "US-ASCII";
+
+/// Engine for base64 payloads. Padding is optional and whitespace is
+/// skipped, since data URLs found in HTML and CSS are often sloppy.
+const ENGINE: Engine = Engine::STANDARD
+    .with_decode_padding_mode(DecodePaddingMode::Indifferent)
+    .with_ignore_whitespace(true);
+
+/// A parsed data URL: its media type, parameters and decoded payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataUrl {
+    media_type: String,
+    parameters: Vec<(String, String)>,
+    base64: bool,
+    data: Vec<u8>,
+}
+
+impl DataUrl {
+    /// Create a base64 data URL with the given media type (e.g. `image/png`).
+    /// The media type is stored in lower case, as [`parse`] does.
+    pub fn new(media_type: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        let mut media_type = media_type.into();
+        media_type.make_ascii_lowercase();
+        Self {
+            media_type,
+            parameters: Vec::new(),
+            base64: true,
+            data: data.into(),
+        }
+    }
+
+    /// Add a media type parameter, such as `charset=utf-8`.
+    /// The name is stored in lower case, as [`parse`] does.
+    pub fn with_parameter(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let mut name = name.into();
+        name.make_ascii_lowercase();
+        self.parameters.push((name, value.into()));
+        self
+    }
+
+    /// Set whether the payload is written as base64 or percent-encoded.
+    pub fn with_base64(self, base64: bool) -> Self {
+        Self { base64, ..self }
+    }
+
+    /// The media type in lower case, `text/plain` if the URL omits it.
+    pub fn media_type(&self) -> &str {
+        &self.media_type
+    }
+
+    /// The media type parameters as `(name, value)` pairs, in order of
+    /// appearance. Names are in lower case and values are kept as written.
+    pub fn parameters(&self) -> &[(String, String)] {
+        &self.parameters
+    }
+
+    /// The value of the parameter with the given name, if present.
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether the payload is base64 rather than percent-encoded.
+    pub fn is_base64(&self) -> bool {
+        self.base64
+    }
+
+    /// The decoded payload.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consume the URL and return the decoded payload.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+/// Writes the data URL, encoding the payload as base64 or percent-encoding it.
+impl fmt::Display for DataUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{SCHEME}{}", self.media_type)?;
+        for (name, value) in &self.parameters {
+            write!(f, ";{name}={value}")?;
+        }
+        if self.base64 {
+            write!(f, ";base64,{}", Engine::STANDARD.encode(&self.data))
+        } else {
+            f.write_str(",")?;
+            for &byte in &self.data {
+                if is_url_safe(byte) {
+                    write!(f, "{}", byte as char)?;
+                } else {
+                    write!(f, "%{byte:02X}")?;
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Every variant except [`DataUrlError::MissingScheme`] carries the byte offset
+/// in the input where the problem was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DataUrlError {
+    /// The input does not start with `data:`.
+    MissingScheme,
+    /// There is no `,` separating the media type from the payload.
+    /// The offset points at the end of the input.
+    MissingComma { offset: usize },
+    /// The media type is not of the form `type/subtype`.
+    InvalidMediaType { offset: usize },
+    /// A parameter is not of the form `name=value`.
+    InvalidParameter { offset: usize },
+    /// A `%` is not followed by two hexadecimal digits.
+    InvalidPercentEncoding { offset: usize },
+    /// The base64 payload is invalid. Its offset is relative to the whole input.
+    Decode(DecodeError),
+}
+
+impl DataUrlError {
+    /// The byte offset in the input where the problem was found, which is the
+    /// start of the input for a missing scheme.
+    pub const fn offset(&self) -> usize {
+        match *self {
+            Self::MissingScheme => 0,
+            Self::MissingComma { offset }
+            | Self::InvalidMediaType { offset }
+            | Self::InvalidParameter { offset }
+            | Self::InvalidPercentEncoding { offset } => offset,
+            Self::Decode(ref err) => err.offset(),
+        }
+    }
+}
+
+impl fmt::Display for DataUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingScheme => f.write_str("data URL does not start with 'data:'"),
+            Self::MissingComma { offset } => {
+                write!(f, "data URL has no ',' before its data at offset {offset}")
+            }
+            Self::InvalidMediaType { offset } => {
+                write!(f, "invalid media type at offset {offset}")
+            }
+            Self::InvalidParameter { offset } => {
+                write!(f, "invalid media type parameter at offset {offset}")
+            }
+            Self::InvalidPercentEncoding { offset } => {
+                write!(f, "invalid percent-encoding at offset {offset}")
+            }
+            Self::Decode(err) => write!(f, "invalid data URL payload: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for DataUrlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Decode(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a data URL and decode its payload.
+pub fn parse(input: &str) -> Result<DataUrl, DataUrlError> {
+    let scheme = input
+        .get(..SCHEME.len())
+        .ok_or(DataUrlError::MissingScheme)?;
+    if !scheme.eq_ignore_ascii_case(SCHEME) {
+        return Err(DataUrlError::MissingScheme);
+    }
+    let header_len = input[SCHEME.len()..]
+        .find(',')
+        .ok_or(DataUrlError::MissingComma {
+            offset: input.len(),
+        })?;
+    let header = &input[SCHEME.len()..SCHEME.len() + header_len];
+    let payload_start = SCHEME.len() + header_len + 1;
+    let payload = &input.as_bytes()[payload_start..];
+
+    // Split the header into the media type and `;`-separated parameters,
+    // keeping the offset of each part
+    let mut parts = header.split(';').scan(SCHEME.len(), |offset, part| {
+        let start = *offset;
+        *offset += part.len() + 1;
+        Some((start, part))
+    });
+    let (media_type_offset, media_type) = parts.next().expect("split yields at least one part");
+    let mut parameters: Vec<(String, String)> = Vec::new();
+    let mut base64 = false;
+    for (offset, part) in parts {
+        if base64 {
+            // `;base64` must be the last part of the header
+            return Err(DataUrlError::InvalidParameter { offset: offset - 1 });
+        }
+        if part.eq_ignore_ascii_case("base64") {
+            base64 = true;
+            continue;
+        }
+        let (name, value) = part
+            .split_once('=')
+            .filter(|(name, _)| is_token(name))
+            .ok_or(DataUrlError::InvalidParameter { offset })?;
+        parameters.push((name.to_ascii_lowercase(), value.to_string()));
+    }
+
+    let media_type = if media_type.is_empty() {
+        if parameters.is_empty() {
+            parameters.push(("charset".to_string(), DEFAULT_CHARSET.to_string()));
+        }
+        DEFAULT_MEDIA_TYPE.to_string()
+    } else {
+        match media_type.split_once('/') {
+            Some((kind, subtype)) if is_token(kind) && is_token(subtype) => {
+                media_type.to_ascii_lowercase()
+            }
+            _ => {
+                return Err(DataUrlError::InvalidMediaType {
+                    offset: media_type_offset,
+                });
+            }
+        }
+    };
+
+    let data = if base64 {
+        decode_bytes(payload, &ENGINE)
+            .map_err(|err| DataUrlError::Decode(err.map_offset(|offset| payload_start + offset)))?
+            .into_vec()
+    } else {
+        percent_decode(payload).map_err(|offset| DataUrlError::InvalidPercentEncoding {
+            offset: payload_start + offset,
+        })?
+    };
+
+    Ok(DataUrl {
+        media_type,
+        parameters,
+        base64,
+        data,
+    })
+}
+
+/// Build a base64 data URL from a media type (e.g. `image/png`) and data.
+pub fn encode(media_type: &str, data: &[u8]) -> String {
+    DataUrl::new(media_type, data).to_string()
+}
+
+/// Whether `s` is a non-empty RFC 2045 token: printable ASCII without spaces
+/// or special characters.
+fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?=".contains(&b))
+}
+
+/// Whether `byte` can appear in a percent-encoded payload as is.
+fn is_url_safe(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:@/?".contains(&byte)
+}
+
+/// Decode `%XX` escapes, leaving other bytes as they are.
+/// Returns the offset of the first malformed escape on error.
+fn percent_decode(input: &[u8]) -> Result<Vec<u8>, usize> {
+    let hex_value = |byte: u8| (byte as char).to_digit(16).map(|d| d as u8);
+    let mut output = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        if input[i] == b'%' {
+            let high = input.get(i + 1).copied().and_then(hex_value).ok_or(i)?;
+            let low = input.get(i + 2).copied().and_then(hex_value).ok_or(i)?;
+            output.push(high << 4 | low);
+            i += 3;
+        } else {
+            output.push(input[i]);
+            i += 1;
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_base64() {
+        let url = parse("data:image/PNG;name=dot.png;base64,iVBORw0K").unwrap();
+        assert_eq!(url.media_type(), "image/png");
+        assert_eq!(url.parameter("NAME"), Some("dot.png"));
+        assert!(url.is_base64());
+        assert_eq!(url.data(), b"\x89PNG\r\n");
+
+        // Unpadded and wrapped payloads are accepted
+        let url = parse("DATA:text/plain;base64,YW\nE").unwrap();
+        assert_eq!(url.into_data(), b"aa");
+    }
+
+    #[test]
+    fn test_parse_percent_encoded() {
+        let url = parse("data:,A%20brief%20note").unwrap();
+        assert_eq!(url.media_type(), "text/plain");
+        assert_eq!(url.parameters(), [("charset".into(), "US-ASCII".into())]);
+        assert!(!url.is_base64());
+        assert_eq!(url.data(), b"A brief note");
+
+        let url = parse("data:;charset=utf-8,%E4%B8%96").unwrap();
+        assert_eq!(url.media_type(), "text/plain");
+        assert_eq!(url.parameters(), [("charset".into(), "utf-8".into())]);
+        assert_eq!(url.data(), "世".as_bytes());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("http://x"), Err(DataUrlError::MissingScheme));
+        assert_eq!(
+            parse("data:text/plain"),
+            Err(DataUrlError::MissingComma { offset: 15 })
+        );
+        assert_eq!(
+            parse("data:text,abc"),
+            Err(DataUrlError::InvalidMediaType { offset: 5 })
+        );
+        assert_eq!(
+            parse("data:text/plain;charset,abc"),
+            Err(DataUrlError::InvalidParameter { offset: 16 })
+        );
+        assert_eq!(
+            parse("data:text/plain;base64;a=b,abc"),
+            Err(DataUrlError::InvalidParameter { offset: 22 })
+        );
+        assert_eq!(
+            parse("data:,100%"),
+            Err(DataUrlError::InvalidPercentEncoding { offset: 9 })
+        );
+        assert_eq!(
+            parse("data:;base64,YW!h"),
+            Err(DataUrlError::Decode(DecodeError::InvalidByte {
+                offset: 15,
+                byte: b'!'
+            }))
+        );
+    }
+
+    #[test]
+    fn test_build() {
+        assert_eq!(encode("image/gif", b"GIF"), "data:image/gif;base64,R0lG");
+
+        let url = DataUrl::new("text/plain", "50% off #1".as_bytes())
+            .with_parameter("charset", "utf-8")
+            .with_base64(false);
+        let text = url.to_string();
+        assert_eq!(text, "data:text/plain;charset=utf-8,50%25%20off%20%231");
+        assert_eq!(parse(&text).unwrap(), url);
+
+        // Built and parsed URLs agree on the case of the media type and
+        // parameter names
+        let url = DataUrl::new("Image/PNG", b"\x89PNG".as_slice());
+        assert_eq!(url.media_type(), "image/png");
+        assert_eq!(parse(&url.to_string()).unwrap(), url);
+        let url = DataUrl::new("text/plain", b"hi".as_slice()).with_parameter("Charset", "utf-8");
+        assert_eq!(url.parameters(), [("charset".into(), "utf-8".into())]);
+        assert_eq!(parse(&url.to_string()).unwrap(), url);
+    }
+}
@@ -1,117 +1,430 @@
-use crate::{PAD_CHAR, get_table_index};
+use std::fmt;
 
-#[derive(Debug, PartialEq, Eq)]
+use crate::PAD_CHAR;
+use crate::engine::{DecodePaddingMode, Engine};
+
+/// Every variant carries the byte offset in the input of the symbol that
+/// caused the error.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DecodeError {
     /// The input length (after trimming padding) is invalid for decoding.
-    /// This occurs when the length mod 4 is 1 (after trimming padding).
-    InputLength,
+    /// For base64 this occurs when the length mod 4 is 1.
+    /// The offset points at the first symbol of the incomplete final chunk,
+    /// which holds `remainder` symbols.
+    InputLength { offset: usize, remainder: usize },
     /// Padding character found in a non-final chunk, or incorrect amount of
-    /// trailing padding characters for the final chunk.
-    WrongPadding,
-    /// An invalid base64 character was encountered (byte value returned).
-    InvalidByte(u8),
+    /// trailing padding characters for the final chunk. The offset points at
+    /// the misplaced padding character, or at the end of the input if padding
+    /// is missing.
+    WrongPadding { offset: usize },
+    /// A character outside the alphabet was encountered.
+    InvalidByte { offset: usize, byte: u8 },
+    /// The final symbol has non-zero bits that do not belong to any output
+    /// byte. Only reported by canonical engines.
+    TrailingBits { offset: usize },
+    /// The checksum carried by the input does not match the decoded data, or
+    /// is missing. The offset points at the checksum if it is written
+    /// separately, or at the start of the input if it is mixed into the data.
+    InvalidChecksum { offset: usize },
+    /// A group of symbols encodes a value too large for the bytes it stands
+    /// for. The offset points at the first symbol of the group.
+    OutOfRange { offset: usize },
+    /// The input opens with a delimiter, such as Ascii85's `<~`, but does not
+    /// end with the matching closing one. The offset points at the end of the
+    /// input.
+    MissingDelimiter { offset: usize },
 }
 
-/// Decode input base64 bytes into original bytes.
-/// Returns `None` if the input is invalid.
-fn decode_bytes(input_bytes: &[u8]) -> Result<Box<[u8]>, DecodeError> {
-    // Trim trailing padding characters first
-    let (input_bytes, trailing_len) = {
-        let mut end = input_bytes.len();
-        while end > 0 {
-            if input_bytes[end - 1] == PAD_CHAR {
-                end -= 1;
-            } else {
-                break;
+impl DecodeError {
+    /// The byte offset in the input of the symbol that caused the error.
+    pub const fn offset(&self) -> usize {
+        match *self {
+            Self::InputLength { offset, .. }
+            | Self::WrongPadding { offset }
+            | Self::InvalidByte { offset, .. }
+            | Self::TrailingBits { offset }
+            | Self::InvalidChecksum { offset }
+            | Self::OutOfRange { offset }
+            | Self::MissingDelimiter { offset } => offset,
+        }
+    }
+
+    /// Replace the offset using `f`, for errors found in a transformed or
+    /// partial view of a larger input.
+    pub(crate) fn map_offset(self, f: impl FnOnce(usize) -> usize) -> Self {
+        match self {
+            Self::InputLength { offset, remainder } => Self::InputLength {
+                offset: f(offset),
+                remainder,
+            },
+            Self::WrongPadding { offset } => Self::WrongPadding { offset: f(offset) },
+            Self::InvalidByte { offset, byte } => Self::InvalidByte {
+                offset: f(offset),
+                byte,
+            },
+            Self::TrailingBits { offset } => Self::TrailingBits { offset: f(offset) },
+            Self::InvalidChecksum { offset } => Self::InvalidChecksum { offset: f(offset) },
+            Self::OutOfRange { offset } => Self::OutOfRange { offset: f(offset) },
+            Self::MissingDelimiter { offset } => Self::MissingDelimiter { offset: f(offset) },
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::InputLength { offset, remainder } => write!(
+                f,
+                "invalid input length: {remainder} symbol(s) at offset {offset} do not form a whole byte"
+            ),
+            Self::WrongPadding { offset } => write!(f, "invalid padding at offset {offset}"),
+            Self::InvalidByte { offset, byte } => write!(
+                f,
+                "invalid symbol '{}' at offset {offset}",
+                byte.escape_ascii()
+            ),
+            Self::TrailingBits { offset } => {
+                write!(f, "non-zero trailing bits in symbol at offset {offset}")
+            }
+            Self::InvalidChecksum { offset } => {
+                write!(f, "checksum mismatch at offset {offset}")
+            }
+            Self::OutOfRange { offset } => {
+                write!(f, "symbol group at offset {offset} is out of range")
+            }
+            Self::MissingDelimiter { offset } => {
+                write!(f, "missing closing delimiter at offset {offset}")
             }
         }
-        (&input_bytes[..end], input_bytes.len() - end)
-    };
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeSliceError {
+    /// The input is not valid for the encoding.
+    DecodeError(DecodeError),
+    /// The output slice is too short to hold the decoded output.
+    OutputSliceTooSmall,
+}
+
+impl From<DecodeError> for DecodeSliceError {
+    fn from(err: DecodeError) -> Self {
+        Self::DecodeError(err)
+    }
+}
+
+impl fmt::Display for DecodeSliceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DecodeError(err) => err.fmt(f),
+            Self::OutputSliceTooSmall => f.write_str("output slice is too small"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeSliceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::DecodeError(err) => Some(err),
+            Self::OutputSliceTooSmall => None,
+        }
+    }
+}
+
+/// Calculate an upper bound on the decoded length of `encoded_len` base64
+/// symbols, whether padded or not. Useful for sizing buffers up front.
+pub const fn decoded_len_estimate(encoded_len: usize) -> usize {
+    // Rounding up to whole chunks cannot overflow, as 3 / 4 of usize::MAX fits
+    encoded_len.div_ceil(4) * 3
+}
+
+/// Calculate the exact decoded length of base64 input from its length and
+/// trailing padding. The symbols themselves and the amount of padding are not
+/// validated, so decoding may still fail.
+pub const fn decoded_len_exact(input_bytes: &[u8]) -> Result<usize, DecodeError> {
+    // Ignore trailing padding characters
+    let mut end = input_bytes.len();
+    while end > 0 && input_bytes[end - 1] == PAD_CHAR {
+        end -= 1;
+    }
+    match end % 4 {
+        0 => Ok(end / 4 * 3),
+        1 => Err(DecodeError::InputLength {
+            offset: end - 1,
+            remainder: 1,
+        }),
+        // Two symbols form 1 byte, three symbols form 2 bytes
+        n => Ok(end / 4 * 3 + n - 1),
+    }
+}
+
+/// Decode input base64 bytes into original bytes.
+/// Returns a [`DecodeError`] if the input is invalid.
+pub fn decode_bytes(input_bytes: &[u8], engine: &Engine) -> Result<Box<[u8]>, DecodeError> {
+    with_symbols(input_bytes, engine.ignore_whitespace(), |symbols| {
+        let (symbols, output_len) = split_padding(symbols, engine)?;
+        let mut output_bytes = vec![0u8; output_len].into_boxed_slice();
+        decode_unpadded(symbols, output_len, &mut output_bytes, engine)?;
+        Ok(output_bytes)
+    })
+}
+
+/// Decode input base64 bytes into the front of a caller-provided buffer
+/// without allocating, unless whitespace has to be skipped.
+/// Returns the number of bytes written.
+/// The buffer is left in an unspecified state if decoding fails.
+pub fn decode_slice(
+    input_bytes: &[u8],
+    output_bytes: &mut [u8],
+    engine: &Engine,
+) -> Result<usize, DecodeSliceError> {
+    with_symbols(input_bytes, engine.ignore_whitespace(), |symbols| {
+        let (symbols, output_len) = split_padding(symbols, engine)?;
+        if output_bytes.len() < output_len {
+            return Ok(None);
+        }
+        decode_unpadded(symbols, output_len, output_bytes, engine).map(Some)
+    })?
+    .ok_or(DecodeSliceError::OutputSliceTooSmall)
+}
+
+/// Decode input base64 bytes and append the original bytes to `output_bytes`.
+/// Returns the number of bytes appended. Nothing is appended if decoding fails.
+pub fn decode_to_vec(
+    input_bytes: &[u8],
+    output_bytes: &mut Vec<u8>,
+    engine: &Engine,
+) -> Result<usize, DecodeError> {
+    with_symbols(input_bytes, engine.ignore_whitespace(), |symbols| {
+        let (symbols, output_len) = split_padding(symbols, engine)?;
+        let start = output_bytes.len();
+        output_bytes.resize(start + output_len, 0);
+        decode_unpadded(symbols, output_len, &mut output_bytes[start..], engine).inspect_err(|_| {
+            output_bytes.truncate(start);
+        })
+    })
+}
+
+/// Run `decode` on the meaningful symbols of the input. If whitespace is
+/// ignored and the input contains any, it is stripped first and error
+/// offsets are mapped back to positions in the original input.
+pub(crate) fn with_symbols<T>(
+    input_bytes: &[u8],
+    ignore_whitespace: bool,
+    decode: impl FnOnce(&[u8]) -> Result<T, DecodeError>,
+) -> Result<T, DecodeError> {
+    if !ignore_whitespace {
+        return decode(input_bytes);
+    }
+    with_symbols_skipping(input_bytes, u8::is_ascii_whitespace, decode)
+}
+
+/// Run `decode` on the input with the bytes matching `skip` stripped, mapping
+/// error offsets back to positions in the original input.
+pub(crate) fn with_symbols_skipping<T>(
+    input_bytes: &[u8],
+    skip: impl Fn(&u8) -> bool,
+    decode: impl FnOnce(&[u8]) -> Result<T, DecodeError>,
+) -> Result<T, DecodeError> {
+    if !input_bytes.iter().any(&skip) {
+        return decode(input_bytes);
+    }
+
+    let mut symbols = Vec::with_capacity(input_bytes.len());
+    let mut offsets = Vec::with_capacity(input_bytes.len());
+    for (offset, byte) in input_bytes.iter().enumerate() {
+        if !skip(byte) {
+            symbols.push(*byte);
+            offsets.push(offset);
+        }
+    }
+    decode(&symbols).map_err(|err| {
+        // Errors past the last symbol point at the end of the original input
+        err.map_offset(|offset| offsets.get(offset).copied().unwrap_or(input_bytes.len()))
+    })
+}
+
+/// Trim and validate the trailing padding of the input.
+/// Returns the input without padding and the length of the decoded output.
+fn split_padding<'a>(
+    input_bytes: &'a [u8],
+    engine: &Engine,
+) -> Result<(&'a [u8], usize), DecodeError> {
+    // Trim trailing padding characters first
+    let (input_bytes, trailing_len) = trim_padding(input_bytes);
 
     let (chunks, remainder) = input_bytes.as_chunks::<4>();
 
-    // Calculate output length
-    let output_len = match remainder.len() {
+    // Calculate output length and the canonical amount of padding
+    let (output_len, expected_padding) = match remainder.len() {
         // No remainder bytes, output length only from full chunks
-        0 => 3 * chunks.len(),
+        0 => (3 * chunks.len(), 0),
         // Only one base64 character left. Not enough to form a byte.
-        1 => return Err(DecodeError::InputLength),
-        2 => {
-            if trailing_len < 2 {
-                // Need at least 2 padding characters
-                return Err(DecodeError::WrongPadding);
+        1 => {
+            return Err(DecodeError::InputLength {
+                offset: 4 * chunks.len(),
+                remainder: 1,
+            });
+        }
+        // Two 6-bit values forms 1 byte ((2 * 6) / 8 = 1)
+        2 => (3 * chunks.len() + 1, 2),
+        // Three 6-bit values forms 2 bytes ((3 * 6) / 8 = 2)
+        3 => (3 * chunks.len() + 2, 1),
+        // Can only be length 0, 1, 2, or 3. Guaranteed by as_chunks.
+        _ => unreachable!(),
+    };
+
+    check_padding(
+        input_bytes.len(),
+        trailing_len,
+        expected_padding,
+        engine.decode_padding_mode(),
+    )?;
+    Ok((input_bytes, output_len))
+}
+
+/// Split trailing padding characters off the input.
+/// Returns the input without padding and the number of padding characters.
+pub(crate) fn trim_padding(input_bytes: &[u8]) -> (&[u8], usize) {
+    let mut end = input_bytes.len();
+    while end > 0 && input_bytes[end - 1] == PAD_CHAR {
+        end -= 1;
+    }
+    (&input_bytes[..end], input_bytes.len() - end)
+}
+
+/// Validate `trailing_len` padding characters after `symbols_len` symbols
+/// against the padding mode, where `expected_padding` is the canonical amount.
+pub(crate) fn check_padding(
+    symbols_len: usize,
+    trailing_len: usize,
+    expected_padding: usize,
+    mode: DecodePaddingMode,
+) -> Result<(), DecodeError> {
+    let allowed_padding = match mode {
+        DecodePaddingMode::Required => {
+            if trailing_len == expected_padding {
+                return Ok(());
             }
-            // Two 6-bit values forms 1 byte ((2 * 6) / 8 = 1)
-            3 * chunks.len() + 1
+            expected_padding
         }
-        3 => {
-            if trailing_len < 1 {
-                // Need at least 1 padding character
-                return Err(DecodeError::WrongPadding);
+        DecodePaddingMode::Forbidden => {
+            if trailing_len == 0 {
+                return Ok(());
             }
-            // Three 6-bit values forms 2 bytes ((3 * 6) / 8 = 2)
-            3 * chunks.len() + 2
+            0
+        }
+        DecodePaddingMode::Indifferent => {
+            if trailing_len == 0 || trailing_len == expected_padding {
+                return Ok(());
+            }
+            expected_padding
         }
-        // Can only be length 0, 1, 2, or 3. Guaranteed by as_chunks.
-        _ => unreachable!(),
     };
-    let mut output_bytes = Box::<[u8]>::new_uninit_slice(output_len);
+    // Point at the first excess padding character, or past the end if
+    // padding is missing
+    let offset = if trailing_len > allowed_padding {
+        symbols_len + allowed_padding
+    } else {
+        symbols_len + trailing_len
+    };
+    Err(DecodeError::WrongPadding { offset })
+}
 
-    // Helper closure to return table index or invalid byte error
-    let get_index = |b: u8| -> Result<u8, DecodeError> {
-        get_table_index(b).ok_or(DecodeError::InvalidByte(b))
+/// Decode input base64 bytes into the front of `output_bytes` and return the
+/// number of bytes written. The caller must make sure `output_bytes` is long
+/// enough for the decoded output.
+pub(crate) fn decode_into(
+    input_bytes: &[u8],
+    output_bytes: &mut [u8],
+    engine: &Engine,
+) -> Result<usize, DecodeError> {
+    let (input_bytes, output_len) = split_padding(input_bytes, engine)?;
+    decode_unpadded(input_bytes, output_len, output_bytes, engine)
+}
+
+/// Decode base64 symbols whose padding was already validated and trimmed by
+/// [`split_padding`], along with the output length it calculated, into the
+/// front of `output_bytes`. Returns the number of bytes written.
+fn decode_unpadded(
+    input_bytes: &[u8],
+    output_len: usize,
+    output_bytes: &mut [u8],
+    engine: &Engine,
+) -> Result<usize, DecodeError> {
+    let (chunks, remainder) = input_bytes.as_chunks::<4>();
+
+    // Helper closure to return table index of the symbol at the given offset
+    // or an error pointing at that offset
+    let alphabet = engine.alphabet();
+    let get_index = |offset: usize| -> Result<u8, DecodeError> {
+        let byte = input_bytes[offset];
+        alphabet.index_of(byte).ok_or(if byte == PAD_CHAR {
+            DecodeError::WrongPadding { offset }
+        } else {
+            DecodeError::InvalidByte { offset, byte }
+        })
     };
 
     // Process each chunk of 4 bytes
-    for (idx, chunk) in chunks.iter().enumerate() {
-        if chunk.contains(&PAD_CHAR) {
-            return Err(DecodeError::WrongPadding);
-        }
-        // Chunk is valid, decode all 4 bytes
-        let idx0 = get_index(chunk[0])?;
-        let idx1 = get_index(chunk[1])?;
-        let idx2 = get_index(chunk[2])?;
-        let idx3 = get_index(chunk[3])?;
+    for idx in 0..chunks.len() {
+        let chunk_start = 4 * idx;
+        let idx0 = get_index(chunk_start)?;
+        let idx1 = get_index(chunk_start + 1)?;
+        let idx2 = get_index(chunk_start + 2)?;
+        let idx3 = get_index(chunk_start + 3)?;
         let start_idx = 3 * idx;
-        output_bytes[start_idx].write((idx0 << 2) | (idx1 >> 4));
-        output_bytes[start_idx + 1].write((idx1 << 4) | (idx2 >> 2));
-        output_bytes[start_idx + 2].write((idx2 << 6) | idx3);
+        output_bytes[start_idx] = (idx0 << 2) | (idx1 >> 4);
+        output_bytes[start_idx + 1] = (idx1 << 4) | (idx2 >> 2);
+        output_bytes[start_idx + 2] = (idx2 << 6) | idx3;
     }
 
     // Process remainder bytes
+    let canonical = engine.canonical();
+    let remainder_start = 4 * chunks.len();
     match remainder.len() {
         0 => {}
         2 => {
-            let idx0 = get_index(remainder[0])?;
-            let idx1 = get_index(remainder[1])?;
+            let idx0 = get_index(remainder_start)?;
+            let idx1 = get_index(remainder_start + 1)?;
+            // Only the high 2 bits of the second symbol are used
+            if canonical && idx1 & 0b1111 != 0 {
+                return Err(DecodeError::TrailingBits {
+                    offset: remainder_start + 1,
+                });
+            }
             let start_index = 3 * chunks.len();
-            output_bytes[start_index].write((idx0 << 2) | (idx1 >> 4));
+            output_bytes[start_index] = (idx0 << 2) | (idx1 >> 4);
         }
         3 => {
-            let idx0 = get_index(remainder[0])?;
-            let idx1 = get_index(remainder[1])?;
-            let idx2 = get_index(remainder[2])?;
+            let idx0 = get_index(remainder_start)?;
+            let idx1 = get_index(remainder_start + 1)?;
+            let idx2 = get_index(remainder_start + 2)?;
+            // Only the high 4 bits of the third symbol are used
+            if canonical && idx2 & 0b11 != 0 {
+                return Err(DecodeError::TrailingBits {
+                    offset: remainder_start + 2,
+                });
+            }
             let start_index = 3 * chunks.len();
-            output_bytes[start_index].write((idx0 << 2) | (idx1 >> 4));
-            output_bytes[start_index + 1].write((idx1 << 4) | (idx2 >> 2));
+            output_bytes[start_index] = (idx0 << 2) | (idx1 >> 4);
+            output_bytes[start_index + 1] = (idx1 << 4) | (idx2 >> 2);
         }
         _ => unreachable!(),
     };
 
-    // SAFETY: All elements of output_bytes have been initialized.
-    let output_bytes = unsafe { output_bytes.assume_init() };
-
-    // Truncate output bytes to actual output length
-    Ok(output_bytes)
+    Ok(output_len)
 }
 
 /// Decode input base64 string into original string.
 /// This function tries to decode the input string as UTF-8 after decoding the base64 bytes.
 /// Replacement characters will be used for invalid UTF-8 sequences.
-/// Returns `None` if the input is invalid.
-pub fn decode_string(input_string: &str) -> Result<String, DecodeError> {
+/// Returns a [`DecodeError`] if the input is invalid.
+pub fn decode_string(input_string: &str, engine: &Engine) -> Result<String, DecodeError> {
     let input_bytes = input_string.as_bytes();
-    let output_bytes = decode_bytes(input_bytes)?;
+    let output_bytes = decode_bytes(input_bytes, engine)?;
     Ok(String::from_utf8_lossy(&output_bytes).to_string())
 }
 
@@ -135,34 +448,226 @@ mod tests {
         ];
 
         for (enc, expected) in cases {
-            let got = decode_bytes(enc)
+            let got = decode_bytes(enc, &Engine::STANDARD)
                 .unwrap_or_else(|e| panic!("Decoding failed for {:?}: {:?}", enc, e));
             assert_eq!(&*got, *expected);
         }
     }
 
     #[test]
-    fn test_decode_valid_with_padding() {
-        assert_eq!(decode_bytes(b"Zig=="), decode_bytes(b"Zig==="));
+    fn test_decode_excess_padding() {
+        assert_eq!(
+            decode_bytes(b"Zig===", &Engine::STANDARD),
+            Err(DecodeError::WrongPadding { offset: 4 })
+        );
+        assert_eq!(
+            decode_bytes(b"YWFh=", &Engine::STANDARD),
+            Err(DecodeError::WrongPadding { offset: 4 })
+        );
+    }
+
+    #[test]
+    fn test_decode_padding_modes() {
+        let required = Engine::STANDARD;
+        let forbidden = Engine::STANDARD.with_decode_padding_mode(DecodePaddingMode::Forbidden);
+        let indifferent = Engine::STANDARD.with_decode_padding_mode(DecodePaddingMode::Indifferent);
+
+        assert_eq!(&*decode_bytes(b"YWE=", &required).unwrap(), b"aa");
+        assert_eq!(
+            decode_bytes(b"YWE", &required),
+            Err(DecodeError::WrongPadding { offset: 3 })
+        );
+
+        assert_eq!(&*decode_bytes(b"YWE", &forbidden).unwrap(), b"aa");
+        assert_eq!(
+            decode_bytes(b"YWE=", &forbidden),
+            Err(DecodeError::WrongPadding { offset: 3 })
+        );
+
+        assert_eq!(&*decode_bytes(b"YWE", &indifferent).unwrap(), b"aa");
+        assert_eq!(&*decode_bytes(b"YWE=", &indifferent).unwrap(), b"aa");
+        assert_eq!(
+            decode_bytes(b"YQ=", &indifferent),
+            Err(DecodeError::WrongPadding { offset: 3 })
+        );
     }
 
     #[test]
     fn test_decode_invalid_byte() {
-        assert_eq!(decode_bytes(b"Zig!"), Err(DecodeError::InvalidByte(b'!')));
-        assert_eq!(decode_bytes(b"Zig!"), Err(DecodeError::InvalidByte(b'!')));
+        assert_eq!(
+            decode_bytes(b"Zig!", &Engine::STANDARD),
+            Err(DecodeError::InvalidByte {
+                offset: 3,
+                byte: b'!'
+            })
+        );
+        assert_eq!(
+            decode_bytes(b"Zig!", &Engine::STANDARD),
+            Err(DecodeError::InvalidByte {
+                offset: 3,
+                byte: b'!'
+            })
+        );
     }
 
     #[test]
     fn test_decode_wrong_padding_in_middle() {
-        assert_eq!(decode_bytes(b"ab==cdef"), Err(DecodeError::WrongPadding));
-        assert_eq!(decode_bytes(b"abcd==ef"), Err(DecodeError::WrongPadding));
-        assert_eq!(decode_bytes(b"abcdef="), Err(DecodeError::WrongPadding));
-        assert_eq!(decode_bytes(b"abcdefg"), Err(DecodeError::WrongPadding));
+        assert_eq!(
+            decode_bytes(b"ab==cdef", &Engine::STANDARD),
+            Err(DecodeError::WrongPadding { offset: 2 })
+        );
+        assert_eq!(
+            decode_bytes(b"abcd==ef", &Engine::STANDARD),
+            Err(DecodeError::WrongPadding { offset: 4 })
+        );
+        assert_eq!(
+            decode_bytes(b"abcdef=", &Engine::STANDARD),
+            Err(DecodeError::WrongPadding { offset: 7 })
+        );
+        assert_eq!(
+            decode_bytes(b"abcdefg", &Engine::STANDARD),
+            Err(DecodeError::WrongPadding { offset: 7 })
+        );
     }
 
     #[test]
     fn test_decode_invalid_length_single_char() {
-        assert_eq!(decode_bytes(b"a"), Err(DecodeError::InputLength));
-        assert_eq!(decode_bytes(b"abcde"), Err(DecodeError::InputLength));
+        assert_eq!(
+            decode_bytes(b"a", &Engine::STANDARD),
+            Err(DecodeError::InputLength {
+                offset: 0,
+                remainder: 1
+            })
+        );
+        assert_eq!(
+            decode_bytes(b"abcde", &Engine::STANDARD),
+            Err(DecodeError::InputLength {
+                offset: 4,
+                remainder: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_trailing_bits() {
+        // Lenient decoding drops the unused bits
+        assert_eq!(&*decode_bytes(b"YR==", &Engine::STANDARD).unwrap(), b"a");
+        assert_eq!(&*decode_bytes(b"YWF=", &Engine::STANDARD).unwrap(), b"aa");
+
+        let canonical = Engine::STANDARD.with_canonical(true);
+        assert_eq!(&*decode_bytes(b"YQ==", &canonical).unwrap(), b"a");
+        assert_eq!(
+            decode_bytes(b"YR==", &canonical),
+            Err(DecodeError::TrailingBits { offset: 1 })
+        );
+        assert_eq!(
+            decode_bytes(b"YWFhYWF=", &canonical),
+            Err(DecodeError::TrailingBits { offset: 6 })
+        );
+    }
+
+    #[test]
+    fn test_decode_slice() {
+        let mut buf = [0u8; 4];
+        assert_eq!(
+            decode_slice(b"YWFhYQ==", &mut buf, &Engine::STANDARD),
+            Ok(4)
+        );
+        assert_eq!(&buf, b"aaaa");
+        assert_eq!(
+            decode_slice(b"YWFhYWE=", &mut buf, &Engine::STANDARD),
+            Err(DecodeSliceError::OutputSliceTooSmall)
+        );
+        assert_eq!(
+            decode_slice(b"YW!=", &mut buf, &Engine::STANDARD),
+            Err(DecodeSliceError::DecodeError(DecodeError::InvalidByte {
+                offset: 2,
+                byte: b'!'
+            }))
+        );
+    }
+
+    #[test]
+    fn test_decode_to_vec_appends() {
+        let mut output = b"prefix:".to_vec();
+        assert_eq!(
+            decode_to_vec(b"YWE=", &mut output, &Engine::STANDARD),
+            Ok(2)
+        );
+        assert_eq!(
+            decode_to_vec(b"YW!=", &mut output, &Engine::STANDARD),
+            Err(DecodeError::InvalidByte {
+                offset: 2,
+                byte: b'!'
+            })
+        );
+        assert_eq!(output, b"prefix:aa");
+    }
+
+    #[test]
+    fn test_decoded_len() {
+        for len in 0..20 {
+            let padded = Engine::STANDARD.encode(&vec![0u8; len]);
+            let unpadded = Engine::STANDARD_NO_PAD.encode(&vec![0u8; len]);
+            assert_eq!(decoded_len_exact(padded.as_bytes()), Ok(len));
+            assert_eq!(decoded_len_exact(unpadded.as_bytes()), Ok(len));
+            assert!(decoded_len_estimate(padded.len()) >= len);
+            assert!(decoded_len_estimate(unpadded.len()) >= len);
+        }
+        assert_eq!(
+            decoded_len_exact(b"abcde"),
+            Err(DecodeError::InputLength {
+                offset: 4,
+                remainder: 1
+            })
+        );
+        assert_eq!(decoded_len_estimate(usize::MAX), usize::MAX / 4 * 3 + 3);
+
+        const LEN: usize = decoded_len_estimate(43);
+        assert_eq!(LEN, 33);
+    }
+
+    #[test]
+    fn test_decode_error_display() {
+        let err = decode_bytes(b"YW\nh", &Engine::STANDARD).unwrap_err();
+        assert_eq!(err.offset(), 2);
+        assert_eq!(err.to_string(), "invalid symbol '\\n' at offset 2");
+        assert_eq!(
+            DecodeError::WrongPadding { offset: 7 }.to_string(),
+            "invalid padding at offset 7"
+        );
+    }
+
+    #[test]
+    fn test_decode_ignoring_whitespace() {
+        let engine = Engine::STANDARD.with_ignore_whitespace(true);
+        assert_eq!(
+            &*decode_bytes(b"YWFh\r\nYWFh\n YQ =\t=\n", &engine).unwrap(),
+            b"aaaaaaa"
+        );
+        assert_eq!(
+            decode_bytes(b"YWFh\nYW=", &Engine::STANDARD),
+            Err(DecodeError::InvalidByte {
+                offset: 4,
+                byte: b'\n'
+            })
+        );
+
+        // Offsets point into the original input
+        assert_eq!(
+            decode_bytes(b"YQ==\r\nYWFh", &engine),
+            Err(DecodeError::WrongPadding { offset: 2 })
+        );
+        assert_eq!(
+            decode_bytes(b"YW\n\nF!", &engine),
+            Err(DecodeError::InvalidByte {
+                offset: 5,
+                byte: b'!'
+            })
+        );
+        assert_eq!(
+            decode_bytes(b"YWE\n", &engine),
+            Err(DecodeError::WrongPadding { offset: 4 })
+        );
     }
 }
@@ -1,67 +1,204 @@
-use crate::{N, PAD_CHAR, TABLE};
+use std::fmt;
+
+use crate::PAD_CHAR;
+use crate::engine::{Engine, LineWrap};
 
 const MASK_6_BITS: u8 = 0b0011_1111;
 
-/// Encode input bytes into base64 bytes.
-fn encode_bytes(input_bytes: &[u8]) -> Box<[u8]> {
-    let (chunks, remainder) = input_bytes.as_chunks::<3>();
+/// Errors for encodings that cannot encode every input, such as Z85.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodeError {
+    /// The input length is not a multiple of the group size. `remainder`
+    /// bytes are left over, starting at `offset`.
+    InputLength { offset: usize, remainder: usize },
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InputLength { offset, remainder } => write!(
+                f,
+                "input ends with {remainder} bytes at offset {offset} that do not fill a group"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodeSliceError {
+    /// The input cannot be encoded.
+    EncodeError(EncodeError),
+    /// The output slice is too short to hold the encoded output.
+    OutputSliceTooSmall,
+}
 
-    // Calculate output length
-    let output_len = if remainder.is_empty() {
-        4 * chunks.len()
-    } else {
-        4 * chunks.len() + 4
+impl From<EncodeError> for EncodeSliceError {
+    fn from(err: EncodeError) -> Self {
+        Self::EncodeError(err)
+    }
+}
+
+impl fmt::Display for EncodeSliceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EncodeError(err) => err.fmt(f),
+            Self::OutputSliceTooSmall => f.write_str("output slice is too small"),
+        }
+    }
+}
+
+impl std::error::Error for EncodeSliceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::EncodeError(err) => Some(err),
+            Self::OutputSliceTooSmall => None,
+        }
+    }
+}
+
+/// Calculate the length of the base64 encoding of `input_len` bytes, with or
+/// without padding. Returns `None` if the length does not fit in a `usize`.
+pub const fn encoded_len(input_len: usize, padding: bool) -> Option<usize> {
+    let Some(chunks_output_len) = (input_len / 3).checked_mul(4) else {
+        return None;
+    };
+    let remainder_output_len = match input_len % 3 {
+        0 => 0,
+        _ if padding => 4,
+        // One byte needs 2 symbols, two bytes need 3 symbols
+        n => n + 1,
     };
-    let mut output_bytes = Box::<[u8]>::new_uninit_slice(output_len);
+    chunks_output_len.checked_add(remainder_output_len)
+}
+
+/// Calculate the encoded length for an input that is already in memory.
+fn encoded_len_for(input_bytes: &[u8], engine: &Engine) -> usize {
+    engine
+        .encoded_len(input_bytes.len())
+        .expect("usize overflow when calculating encoded length")
+}
+
+/// Encode input bytes into base64 bytes using the engine's alphabet.
+/// Padding is appended to a partial final chunk only if the engine asks for it,
+/// and the output is broken into lines if the engine wraps lines.
+pub fn encode_bytes(input_bytes: &[u8], engine: &Engine) -> Box<[u8]> {
+    let output_len = encoded_len_for(input_bytes, engine);
+    let mut output_bytes = vec![0u8; output_len].into_boxed_slice();
+    encode_wrapped(input_bytes, &mut output_bytes, engine);
+    output_bytes
+}
+
+/// Encode input bytes into the front of a caller-provided buffer without
+/// allocating. Returns the number of bytes written.
+pub fn encode_slice(
+    input_bytes: &[u8],
+    output_bytes: &mut [u8],
+    engine: &Engine,
+) -> Result<usize, EncodeSliceError> {
+    match engine.encoded_len(input_bytes.len()) {
+        Some(output_len) if output_len <= output_bytes.len() => {}
+        _ => return Err(EncodeSliceError::OutputSliceTooSmall),
+    }
+    Ok(encode_wrapped(input_bytes, output_bytes, engine))
+}
+
+/// Encode input bytes and append the base64 output to `output_string`.
+/// Reusing the same string across calls avoids repeated allocations.
+pub fn encode_to_string(input_bytes: &[u8], output_string: &mut String, engine: &Engine) {
+    let output_len = encoded_len_for(input_bytes, engine);
+    let start = output_string.len();
+    // SAFETY: The appended bytes are overwritten with ASCII characters from the
+    // alphabet and padding, so the string stays valid UTF-8.
+    let output_bytes = unsafe { output_string.as_mut_vec() };
+    output_bytes.resize(start + output_len, 0);
+    encode_wrapped(input_bytes, &mut output_bytes[start..], engine);
+}
+
+/// Encode input bytes into the front of `output_bytes`, breaking the output
+/// into lines if the engine wraps lines. Returns the number of bytes written.
+/// The caller must make sure `output_bytes` is long enough.
+fn encode_wrapped(input_bytes: &[u8], output_bytes: &mut [u8], engine: &Engine) -> usize {
+    let encoded_len = encode_into(input_bytes, output_bytes, engine);
+    match engine.line_wrap() {
+        Some(line_wrap) => wrap_lines(output_bytes, encoded_len, &line_wrap),
+        None => encoded_len,
+    }
+}
+
+/// Insert line endings into the first `encoded_len` bytes of `output_bytes`,
+/// which must have room for the wrapped output. Returns the wrapped length.
+fn wrap_lines(output_bytes: &mut [u8], encoded_len: usize, line_wrap: &LineWrap) -> usize {
+    let width = line_wrap.width();
+    let ending = line_wrap.ending().as_bytes();
+    let lines = encoded_len.div_ceil(width);
+
+    // Move lines to their final position starting from the last one, so no
+    // line is overwritten before it has been moved
+    for line in (0..lines).rev() {
+        let src = line * width;
+        let len = width.min(encoded_len - src);
+        let dst = line * (width + ending.len());
+        output_bytes.copy_within(src..src + len, dst);
+        if line + 1 < lines || line_wrap.trailing_newline() {
+            output_bytes[dst + len..dst + len + ending.len()].copy_from_slice(ending);
+        }
+    }
+
+    line_wrap
+        .wrapped_len(encoded_len)
+        .expect("wrapped length fits in the output buffer")
+}
+
+/// Encode input bytes into the front of `output_bytes` and return the number
+/// of bytes written. The caller must make sure `output_bytes` is long enough.
+pub(crate) fn encode_into(input_bytes: &[u8], output_bytes: &mut [u8], engine: &Engine) -> usize {
+    let (chunks, remainder) = input_bytes.as_chunks::<3>();
+    let table = engine.alphabet().symbols();
+    // Map a 6-bit value to its base64 character
+    let symbol = |value: u8| table[(value & MASK_6_BITS) as usize];
 
     // Process each chunk of 3 bytes
     for (i, chunk) in chunks.iter().enumerate() {
         let start_idx = 4 * i;
-        output_bytes[start_idx].write(chunk[0] >> 2);
-        output_bytes[start_idx + 1].write((chunk[0] << 4) & MASK_6_BITS | (chunk[1] >> 4));
-        output_bytes[start_idx + 2].write((chunk[1] << 2) & MASK_6_BITS | (chunk[2] >> 6));
-        output_bytes[start_idx + 3].write(chunk[2] & MASK_6_BITS);
+        output_bytes[start_idx] = symbol(chunk[0] >> 2);
+        output_bytes[start_idx + 1] = symbol((chunk[0] << 4) | (chunk[1] >> 4));
+        output_bytes[start_idx + 2] = symbol((chunk[1] << 2) | (chunk[2] >> 6));
+        output_bytes[start_idx + 3] = symbol(chunk[2]);
     }
 
     // Process remainder bytes
-    match remainder.len() {
+    let start_idx = 4 * chunks.len();
+    let written = match remainder.len() {
         // Skip if no remainder
-        0 => {}
+        0 => return start_idx,
         1 => {
-            let start_idx = 4 * chunks.len();
-            output_bytes[start_idx].write(remainder[0] >> 2);
-            output_bytes[start_idx + 1].write((remainder[0] << 4) & MASK_6_BITS);
-            output_bytes[start_idx + 2].write(N);
-            output_bytes[start_idx + 3].write(N);
+            output_bytes[start_idx] = symbol(remainder[0] >> 2);
+            output_bytes[start_idx + 1] = symbol(remainder[0] << 4);
+            2
         }
         2 => {
-            let start_idx = 4 * chunks.len();
-            output_bytes[start_idx].write(remainder[0] >> 2);
-            output_bytes[start_idx + 1]
-                .write((remainder[0] << 4) & MASK_6_BITS | (remainder[1] >> 4));
-            output_bytes[start_idx + 2].write((remainder[1] << 2) & MASK_6_BITS);
-            output_bytes[start_idx + 3].write(N);
+            output_bytes[start_idx] = symbol(remainder[0] >> 2);
+            output_bytes[start_idx + 1] = symbol((remainder[0] << 4) | (remainder[1] >> 4));
+            output_bytes[start_idx + 2] = symbol(remainder[1] << 2);
+            3
         }
         // Can only be length 0, 1, or 2. Guaranteed by as_chunks.
         _ => unreachable!(),
-    }
-
-    // SAFETY: All elements of output_bytes have been initialized.
-    let mut output_bytes = unsafe { output_bytes.assume_init() };
-
-    // Map 6-bit values to base64 characters
-    (0..output_len).for_each(|i| {
-        let table_index = output_bytes[i] as usize;
-        output_bytes[i] = *TABLE.get(table_index).unwrap_or(&PAD_CHAR);
-    });
+    };
 
-    output_bytes
+    if !engine.encode_padding() {
+        return start_idx + written;
+    }
+    output_bytes[start_idx + written..start_idx + 4].fill(PAD_CHAR);
+    start_idx + 4
 }
 
 /// Encode input string into base64 string.
-pub fn encode_string(input_string: &str) -> String {
+pub fn encode_string(input_string: &str, engine: &Engine) -> String {
     let input_bytes = input_string.as_bytes();
-    let output_bytes = encode_bytes(input_bytes);
+    let output_bytes = encode_bytes(input_bytes, engine);
     String::from_utf8_lossy(&output_bytes).to_string()
 }
 
@@ -84,8 +221,87 @@ mod tests {
             (b"YWFhYWFhYWFh", b"aaaaaaaaa"),
         ];
         for (expected, input) in cases {
-            let encoded = encode_bytes(input);
+            let encoded = encode_bytes(input, &Engine::STANDARD);
+            assert_eq!(&encoded[..], *expected);
+        }
+    }
+
+    #[test]
+    fn test_encode_bytes_without_padding() {
+        let engine = Engine::STANDARD.with_encode_padding(false);
+        let cases: &[(&[u8], &[u8])] = &[
+            (b"", b""),
+            (b"YQ", b"a"),
+            (b"YWE", b"aa"),
+            (b"YWFh", b"aaa"),
+            (b"YWFhYQ", b"aaaa"),
+        ];
+        for (expected, input) in cases {
+            let encoded = encode_bytes(input, &engine);
             assert_eq!(&encoded[..], *expected);
         }
     }
+
+    #[test]
+    fn test_encode_slice() {
+        let mut buf = [0u8; 8];
+        assert_eq!(encode_slice(b"aaaa", &mut buf, &Engine::STANDARD), Ok(8));
+        assert_eq!(&buf, b"YWFhYQ==");
+        assert_eq!(encode_slice(b"aa", &mut buf, &Engine::STANDARD), Ok(4));
+        assert_eq!(&buf[..4], b"YWE=");
+        assert_eq!(
+            encode_slice(b"aaaaaaa", &mut buf, &Engine::STANDARD),
+            Err(EncodeSliceError::OutputSliceTooSmall)
+        );
+    }
+
+    #[test]
+    fn test_encode_to_string_appends() {
+        let mut output = String::from("prefix:");
+        encode_to_string(b"aa", &mut output, &Engine::STANDARD);
+        encode_to_string(b"a", &mut output, &Engine::STANDARD_NO_PAD);
+        assert_eq!(output, "prefix:YWE=YQ");
+    }
+
+    #[test]
+    fn test_encoded_len() {
+        for len in 0..20 {
+            let input = vec![0u8; len];
+            assert_eq!(
+                encoded_len(len, true),
+                Some(encode_bytes(&input, &Engine::STANDARD).len())
+            );
+            assert_eq!(
+                encoded_len(len, false),
+                Some(encode_bytes(&input, &Engine::STANDARD_NO_PAD).len())
+            );
+        }
+        assert_eq!(encoded_len(usize::MAX, true), None);
+
+        const LEN: Option<usize> = encoded_len(32, false);
+        assert_eq!(LEN, Some(43));
+    }
+
+    #[test]
+    fn test_encode_bytes_wrapped() {
+        use crate::engine::LineEnding;
+
+        let engine = Engine::STANDARD.with_line_wrap(Some(LineWrap::new(4, LineEnding::Lf)));
+        assert_eq!(&*encode_bytes(b"", &engine), b"");
+        assert_eq!(&*encode_bytes(b"aaa", &engine), b"YWFh");
+        assert_eq!(&*encode_bytes(b"aaaaaaa", &engine), b"YWFh\nYWFh\nYQ==");
+
+        let engine = Engine::STANDARD.with_line_wrap(Some(
+            LineWrap::new(3, LineEnding::CrLf).with_trailing_newline(true),
+        ));
+        assert_eq!(&*encode_bytes(b"aaaa", &engine), b"YWF\r\nhYQ\r\n==\r\n");
+
+        let input = [0u8; 100];
+        let mime = encode_bytes(&input, &Engine::MIME);
+        let lines: Vec<&[u8]> = mime.split(|&b| b == b'\n').collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), 77);
+        assert!(lines[0].ends_with(b"\r"));
+        assert_eq!(Engine::MIME.decode(&mime).unwrap(), input);
+    }
 }
@@ -0,0 +1,469 @@
+//! MIME encoded words in mail headers following
+//! [RFC 2047](https://datatracker.ietf.org/doc/html/rfc2047).
+//!
+//! An encoded word has the form `=?charset?encoding?text?=`, where the
+//! encoding is `B` for base64 or `Q` for a variant of quoted-printable in
+//! which `_` stands for a space. Encoded words are at most 75 characters, so
+//! longer text is split across several words, and whitespace between adjacent
+//! encoded words is not part of the text.
+
+use std::fmt;
+
+use crate::decode::{DecodeError, decode_bytes};
+use crate::encode::encode_bytes;
+use crate::engine::{DecodePaddingMode, Engine};
+
+/// Maximum length of an encoded word, including its delimiters.
+const MAX_WORD_LEN: usize = 75;
+
+/// Separator written between encoded words, which folds the header line.
+const WORD_SEPARATOR: &str = "\r\n ";
+
+/// Engine for `B` encoded text. Some mailers drop the padding, so it is
+/// optional when decoding.
+const ENGINE: Engine = Engine::STANDARD.with_decode_padding_mode(DecodePaddingMode::Indifferent);
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+/// The character sets encoded words can be decoded from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Charset {
+    /// UTF-8, the charset used for encoding.
+    Utf8,
+    /// ISO-8859-1 (Latin-1), where every byte is the code point of the same
+    /// value.
+    Iso8859_1,
+    /// US-ASCII, which only allows bytes below 0x80.
+    UsAscii,
+}
+
+impl Charset {
+    /// The charset name as written in encoded words.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Utf8 => "UTF-8",
+            Self::Iso8859_1 => "ISO-8859-1",
+            Self::UsAscii => "US-ASCII",
+        }
+    }
+
+    /// Look up a charset by name, ignoring case and an RFC 2231 language
+    /// suffix such as `*en`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.split_once('*').map_or(name, |(name, _)| name);
+        [Self::Utf8, Self::Iso8859_1, Self::UsAscii]
+            .into_iter()
+            .find(|charset| charset.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Convert bytes in this charset to a string.
+    /// Returns `None` if the bytes are not valid in this charset.
+    fn decode_text(self, bytes: Vec<u8>) -> Option<String> {
+        match self {
+            Self::Utf8 => String::from_utf8(bytes).ok(),
+            Self::Iso8859_1 => Some(bytes.iter().map(|&byte| byte as char).collect()),
+            Self::UsAscii => bytes.is_ascii().then(|| {
+                // SAFETY: ASCII is valid UTF-8.
+                unsafe { String::from_utf8_unchecked(bytes) }
+            }),
+        }
+    }
+}
+
+/// The encoding of the text of an encoded word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordEncoding {
+    /// Base64, written as `B`. Compact for non-Latin text.
+    Base64,
+    /// Q encoding, written as `Q`. Keeps mostly ASCII text readable.
+    Q,
+}
+
+impl WordEncoding {
+    /// The encoding letter as written in encoded words.
+    const fn letter(&self) -> char {
+        match self {
+            Self::Base64 => 'B',
+            Self::Q => 'Q',
+        }
+    }
+}
+
+/// Every variant carries the byte offset in the header where the problem was
+/// found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodedWordError {
+    /// The charset of an encoded word is not supported.
+    /// The offset points at the charset name.
+    UnsupportedCharset { offset: usize },
+    /// The decoded bytes are not valid text in their charset.
+    /// The offset points at the start of the (first) encoded word.
+    InvalidText { offset: usize },
+    /// The encoded text is invalid. Its offset is relative to the whole header.
+    Decode(DecodeError),
+}
+
+impl EncodedWordError {
+    /// The byte offset in the header where the problem was found.
+    pub const fn offset(&self) -> usize {
+        match *self {
+            Self::UnsupportedCharset { offset } | Self::InvalidText { offset } => offset,
+            Self::Decode(ref err) => err.offset(),
+        }
+    }
+}
+
+impl fmt::Display for EncodedWordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedCharset { offset } => {
+                write!(f, "unsupported charset at offset {offset}")
+            }
+            Self::InvalidText { offset } => {
+                write!(
+                    f,
+                    "encoded word at offset {offset} is not valid in its charset"
+                )
+            }
+            Self::Decode(err) => write!(f, "invalid encoded word: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for EncodedWordError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Decode(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// An encoded word found in a header.
+struct Word<'a> {
+    /// Offset of the `=?` that starts the word.
+    start: usize,
+    /// Offset just past the `?=` that ends the word.
+    end: usize,
+    charset: &'a str,
+    encoding: WordEncoding,
+    /// Offset of the encoded text.
+    text_start: usize,
+    text: &'a str,
+}
+
+/// Parse the encoded word starting at `start`, which points at `=?`.
+/// Returns `None` if the text there is not an encoded word.
+fn parse_word(header: &str, start: usize) -> Option<Word<'_>> {
+    let rest = header.get(start + 2..)?;
+    let (charset, rest) = rest.split_once('?')?;
+    let (encoding, rest) = rest.split_once('?')?;
+    let (text, _) = rest.split_once("?=")?;
+    if charset.is_empty() || !charset.bytes().all(is_token_char) {
+        return None;
+    }
+    let encoding = match encoding {
+        "B" | "b" => WordEncoding::Base64,
+        "Q" | "q" => WordEncoding::Q,
+        _ => return None,
+    };
+    if !text.bytes().all(|byte| byte.is_ascii_graphic()) {
+        return None;
+    }
+    let text_start = start + 2 + charset.len() + 1 + encoding.letter().len_utf8() + 1;
+    Some(Word {
+        start,
+        end: text_start + text.len() + 2,
+        charset,
+        encoding,
+        text_start,
+        text,
+    })
+}
+
+/// Whether `byte` can appear in a charset name.
+fn is_token_char(byte: u8) -> bool {
+    byte.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?.=".contains(&byte)
+}
+
+/// Decode the text of a `Q` encoded word, starting at `offset` in the header.
+fn decode_q(text: &str, offset: usize) -> Result<Vec<u8>, DecodeError> {
+    let hex_value = |byte: u8| (byte as char).to_digit(16).map(|d| d as u8);
+    let text = text.as_bytes();
+    let mut output = Vec::with_capacity(text.len());
+    let mut i = 0;
+    while i < text.len() {
+        match text[i] {
+            b'=' if text.len() - i < 3 => {
+                return Err(DecodeError::InputLength {
+                    offset: offset + i,
+                    remainder: text.len() - i,
+                });
+            }
+            b'=' => {
+                let digit = |i: usize| {
+                    let byte = text[i];
+                    hex_value(byte).ok_or(DecodeError::InvalidByte {
+                        offset: offset + i,
+                        byte,
+                    })
+                };
+                output.push(digit(i + 1)? << 4 | digit(i + 2)?);
+                i += 3;
+            }
+            b'_' => {
+                output.push(b' ');
+                i += 1;
+            }
+            byte => {
+                output.push(byte);
+                i += 1;
+            }
+        }
+    }
+    Ok(output)
+}
+
+/// Decode all encoded words in a header value, joining adjacent encoded words
+/// and dropping the whitespace between them. Text that is not an encoded word
+/// is kept as is.
+pub fn decode(header: &str) -> Result<String, EncodedWordError> {
+    let mut output = String::with_capacity(header.len());
+    // Bytes of adjacent encoded words in the same charset, decoded together
+    // since an encoder may split a character across words
+    let mut pending: Option<(Charset, usize, Vec<u8>)> = None;
+    let flush = |pending: &mut Option<(Charset, usize, Vec<u8>)>, output: &mut String| {
+        if let Some((charset, offset, bytes)) = pending.take() {
+            let text = charset
+                .decode_text(bytes)
+                .ok_or(EncodedWordError::InvalidText { offset })?;
+            output.push_str(&text);
+        }
+        Ok(())
+    };
+
+    let mut position = 0;
+    let mut search = 0;
+    while let Some(found) = header[search..].find("=?") {
+        let start = search + found;
+        let Some(word) = parse_word(header, start) else {
+            search = start + 1;
+            continue;
+        };
+
+        // Whitespace between two encoded words is dropped
+        let between = &header[position..start];
+        if pending.is_none() || !between.bytes().all(|byte| byte.is_ascii_whitespace()) {
+            flush(&mut pending, &mut output)?;
+            output.push_str(between);
+        }
+
+        let charset = Charset::from_name(word.charset)
+            .ok_or(EncodedWordError::UnsupportedCharset { offset: start + 2 })?;
+        let bytes = match word.encoding {
+            WordEncoding::Base64 => decode_bytes(word.text.as_bytes(), &ENGINE)
+                .map(|bytes| bytes.into_vec())
+                .map_err(|err| err.map_offset(|offset| word.text_start + offset)),
+            WordEncoding::Q => decode_q(word.text, word.text_start),
+        }
+        .map_err(EncodedWordError::Decode)?;
+        match &mut pending {
+            Some((pending_charset, _, pending_bytes)) if *pending_charset == charset => {
+                pending_bytes.extend_from_slice(&bytes);
+            }
+            _ => {
+                flush(&mut pending, &mut output)?;
+                pending = Some((charset, word.start, bytes));
+            }
+        }
+        position = word.end;
+        search = word.end;
+    }
+    flush(&mut pending, &mut output)?;
+    output.push_str(&header[position..]);
+    Ok(output)
+}
+
+/// Encode text as UTF-8 encoded words of at most 75 characters, never
+/// splitting a character across words.
+pub fn encode_words(text: &str, encoding: WordEncoding) -> Vec<String> {
+    let prefix = format!("=?{}?{}?", Charset::Utf8.name(), encoding.letter());
+    let max_text_len = MAX_WORD_LEN - prefix.len() - 2;
+    // Base64 text is written in whole 4 symbol chunks of 3 bytes
+    let max_base64_bytes = max_text_len / 4 * 3;
+
+    let mut words = Vec::new();
+    let mut chunk_start = 0;
+    let mut chunk_len = 0;
+    for (index, c) in text.char_indices() {
+        let len = match encoding {
+            WordEncoding::Base64 => c.len_utf8(),
+            WordEncoding::Q => q_encoded_len(c),
+        };
+        let max_len = match encoding {
+            WordEncoding::Base64 => max_base64_bytes,
+            WordEncoding::Q => max_text_len,
+        };
+        if chunk_len + len > max_len {
+            words.push(encode_word(&prefix, &text[chunk_start..index], encoding));
+            chunk_start = index;
+            chunk_len = 0;
+        }
+        chunk_len += len;
+    }
+    if chunk_start < text.len() {
+        words.push(encode_word(&prefix, &text[chunk_start..], encoding));
+    }
+    words
+}
+
+/// Encode text as UTF-8 encoded words, separated by folding whitespace so the
+/// result can be used as a header value.
+pub fn encode(text: &str, encoding: WordEncoding) -> String {
+    encode_words(text, encoding).join(WORD_SEPARATOR)
+}
+
+/// Whether `byte` can be written as is in `Q` encoded text. This is the set
+/// RFC 2047 allows in every header context, including phrases.
+fn is_q_safe(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"!*+-/".contains(&byte)
+}
+
+/// The length of a character in `Q` encoded text.
+fn q_encoded_len(c: char) -> usize {
+    match c {
+        ' ' => 1,
+        c if c.is_ascii() && is_q_safe(c as u8) => 1,
+        c => c.len_utf8() * 3,
+    }
+}
+
+/// Encode one chunk of text as an encoded word.
+fn encode_word(prefix: &str, chunk: &str, encoding: WordEncoding) -> String {
+    let mut word = String::with_capacity(MAX_WORD_LEN);
+    word.push_str(prefix);
+    match encoding {
+        WordEncoding::Base64 => {
+            let encoded = encode_bytes(chunk.as_bytes(), &Engine::STANDARD);
+            // SAFETY: Base64 output is ASCII.
+            word.push_str(unsafe { std::str::from_utf8_unchecked(&encoded) });
+        }
+        WordEncoding::Q => {
+            for &byte in chunk.as_bytes() {
+                if byte == b' ' {
+                    word.push('_');
+                } else if is_q_safe(byte) {
+                    word.push(byte as char);
+                } else {
+                    word.push('=');
+                    word.push(HEX_DIGITS[(byte >> 4) as usize] as char);
+                    word.push(HEX_DIGITS[(byte & 0x0F) as usize] as char);
+                }
+            }
+        }
+    }
+    word.push_str("?=");
+    word
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_rfc_examples() {
+        assert_eq!(decode("(=?ISO-8859-1?Q?a?=)").unwrap(), "(a)");
+        assert_eq!(decode("(=?ISO-8859-1?Q?a?= b)").unwrap(), "(a b)");
+        assert_eq!(
+            decode("(=?ISO-8859-1?Q?a?= =?ISO-8859-1?Q?b?=)").unwrap(),
+            "(ab)"
+        );
+        assert_eq!(
+            decode("(=?ISO-8859-1?Q?a?=\r\n    =?ISO-8859-1?Q?b?=)").unwrap(),
+            "(ab)"
+        );
+        assert_eq!(decode("(=?ISO-8859-1?Q?a_b?=)").unwrap(), "(a b)");
+        assert_eq!(
+            decode("=?ISO-8859-1?Q?Keld_J=F8rn_Simonsen?= <keld@dkuug.dk>").unwrap(),
+            "Keld Jørn Simonsen <keld@dkuug.dk>"
+        );
+        assert_eq!(
+            decode("=?US-ASCII*EN?Q?Keith_Moore?= <moore@cs.utk.edu>").unwrap(),
+            "Keith Moore <moore@cs.utk.edu>"
+        );
+    }
+
+    #[test]
+    fn test_decode_base64() {
+        assert_eq!(
+            decode("Subject: =?UTF-8?B?Y2Fmw6k=?= au lait").unwrap(),
+            "Subject: café au lait"
+        );
+        // Unpadded, lower case encoding letter, and a character split across
+        // two words
+        assert_eq!(
+            decode("=?utf-8?b?Y2Fmww?= =?UTF-8?B?qQ==?=").unwrap(),
+            "café"
+        );
+        // Malformed words are kept as text
+        assert_eq!(
+            decode("=?UTF-8?X?abc?= =?UTF-8?B?YQ").unwrap(),
+            "=?UTF-8?X?abc?= =?UTF-8?B?YQ"
+        );
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(
+            decode("a =?KOI8-R?B?YQ==?="),
+            Err(EncodedWordError::UnsupportedCharset { offset: 4 })
+        );
+        assert_eq!(
+            decode("=?UTF-8?B?Y!==?="),
+            Err(EncodedWordError::Decode(DecodeError::InvalidByte {
+                offset: 11,
+                byte: b'!'
+            }))
+        );
+        assert_eq!(
+            decode("=?UTF-8?Q?a=G1?="),
+            Err(EncodedWordError::Decode(DecodeError::InvalidByte {
+                offset: 12,
+                byte: b'G'
+            }))
+        );
+        assert_eq!(
+            decode("=?UTF-8?Q?ab=F?="),
+            Err(EncodedWordError::Decode(DecodeError::InputLength {
+                offset: 12,
+                remainder: 2
+            }))
+        );
+        assert_eq!(
+            decode("x =?UTF-8?Q?=FF?="),
+            Err(EncodedWordError::InvalidText { offset: 2 })
+        );
+        assert_eq!(
+            decode("=?US-ASCII?Q?=E9?="),
+            Err(EncodedWordError::InvalidText { offset: 0 })
+        );
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode("café", WordEncoding::Base64), "=?UTF-8?B?Y2Fmw6k=?=");
+        assert_eq!(
+            encode("a b=c?_é", WordEncoding::Q),
+            "=?UTF-8?Q?a_b=3Dc=3F=5F=C3=A9?="
+        );
+        assert_eq!(encode("", WordEncoding::Q), "");
+
+        let text = "Grüße aus Köln — ".repeat(8);
+        for encoding in [WordEncoding::Base64, WordEncoding::Q] {
+            let words = encode_words(&text, encoding);
+            assert!(words.len() > 1);
+            assert!(words.iter().all(|word| word.len() <= MAX_WORD_LEN));
+            assert_eq!(decode(&encode(&text, encoding)).unwrap(), text);
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use crate::alphabet::Alphabet;
+use crate::decode::{DecodeError, DecodeSliceError, decode_slice, decode_to_vec};
+use crate::encode::{EncodeSliceError, encode_slice, encode_to_string, encoded_len};
+
+/// How the decoder treats trailing `=` padding characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodePaddingMode {
+    /// Padding must be present, with exactly the canonical amount.
+    Required,
+    /// Padding must not be present.
+    Forbidden,
+    /// Padding may be omitted, but if present it must be the canonical amount.
+    Indifferent,
+}
+
+/// The line ending inserted when wrapping encoded output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`
+    Lf,
+    /// `\r\n`
+    CrLf,
+}
+
+impl LineEnding {
+    /// The bytes of this line ending.
+    pub const fn as_bytes(&self) -> &'static [u8] {
+        match self {
+            Self::Lf => b"\n",
+            Self::CrLf => b"\r\n",
+        }
+    }
+}
+
+/// How encoded output is broken into lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineWrap {
+    width: usize,
+    ending: LineEnding,
+    trailing_newline: bool,
+}
+
+impl LineWrap {
+    /// MIME wrapping from RFC 2045 §6.8: 76 symbols per line, CRLF endings.
+    pub const MIME: LineWrap = LineWrap::new(76, LineEnding::CrLf);
+
+    /// PEM wrapping from RFC 7468 §2: 64 symbols per line, LF endings, with
+    /// the last line terminated as well.
+    pub const PEM: LineWrap = LineWrap::new(64, LineEnding::Lf).with_trailing_newline(true);
+
+    /// Wrap lines after `width` symbols using `ending`, without a line ending
+    /// after the last line.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is zero.
+    pub const fn new(width: usize, ending: LineEnding) -> Self {
+        assert!(width > 0, "line width must be positive");
+        Self {
+            width,
+            ending,
+            trailing_newline: false,
+        }
+    }
+
+    /// Set whether the last line of non-empty output is terminated too.
+    pub const fn with_trailing_newline(self, trailing_newline: bool) -> Self {
+        Self {
+            trailing_newline,
+            ..self
+        }
+    }
+
+    /// The maximum number of symbols per line.
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The line ending inserted between lines.
+    pub const fn ending(&self) -> LineEnding {
+        self.ending
+    }
+
+    /// Whether the last line is terminated with a line ending.
+    pub const fn trailing_newline(&self) -> bool {
+        self.trailing_newline
+    }
+
+    /// Calculate the length of `encoded_len` symbols once wrapped.
+    /// Returns `None` if the length does not fit in a `usize`.
+    pub const fn wrapped_len(&self, encoded_len: usize) -> Option<usize> {
+        let lines = encoded_len.div_ceil(self.width);
+        let line_endings = if self.trailing_newline || lines == 0 {
+            lines
+        } else {
+            lines - 1
+        };
+        match line_endings.checked_mul(self.ending.as_bytes().len()) {
+            Some(endings_len) => encoded_len.checked_add(endings_len),
+            None => None,
+        }
+    }
+}
+
+/// A base64 codec configuration: which alphabet to encode to and decode from,
+/// how padding is produced and validated, and how output is wrapped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Engine {
+    alphabet: Alphabet,
+    encode_padding: bool,
+    decode_padding_mode: DecodePaddingMode,
+    canonical: bool,
+    ignore_whitespace: bool,
+    line_wrap: Option<LineWrap>,
+}
+
+impl Engine {
+    /// Engine using the standard alphabet from RFC 4648 §4.
+    pub const STANDARD: Engine = Engine::new(Alphabet::STANDARD);
+
+    /// Engine using the standard alphabet without padding.
+    pub const STANDARD_NO_PAD: Engine = Engine::STANDARD
+        .with_encode_padding(false)
+        .with_decode_padding_mode(DecodePaddingMode::Forbidden);
+
+    /// Engine using the URL and filename safe alphabet from RFC 4648 §5.
+    pub const URL_SAFE: Engine = Engine::new(Alphabet::URL_SAFE);
+
+    /// Engine using the URL and filename safe alphabet without padding,
+    /// as used by JWTs and most web tokens.
+    pub const URL_SAFE_NO_PAD: Engine = Engine::URL_SAFE
+        .with_encode_padding(false)
+        .with_decode_padding_mode(DecodePaddingMode::Forbidden);
+
+    /// Engine for MIME bodies (RFC 2045): standard alphabet wrapped at 76
+    /// columns with CRLF, decoding with line breaks skipped.
+    pub const MIME: Engine = Engine::STANDARD
+        .with_line_wrap(Some(LineWrap::MIME))
+        .with_ignore_whitespace(true);
+
+    /// Engine for PEM bodies (RFC 7468): standard alphabet wrapped at 64
+    /// columns with LF, decoding with line breaks skipped.
+    pub const PEM: Engine = Engine::STANDARD
+        .with_line_wrap(Some(LineWrap::PEM))
+        .with_ignore_whitespace(true);
+
+    /// Create an engine for the given alphabet.
+    /// Encoding emits padding and decoding requires it.
+    pub const fn new(alphabet: Alphabet) -> Self {
+        Self {
+            alphabet,
+            encode_padding: true,
+            decode_padding_mode: DecodePaddingMode::Required,
+            canonical: false,
+            ignore_whitespace: false,
+            line_wrap: None,
+        }
+    }
+
+    /// Set whether encoding appends `=` padding to a partial final chunk.
+    pub const fn with_encode_padding(self, encode_padding: bool) -> Self {
+        Self {
+            encode_padding,
+            ..self
+        }
+    }
+
+    /// Set how decoding validates trailing `=` padding.
+    pub const fn with_decode_padding_mode(self, decode_padding_mode: DecodePaddingMode) -> Self {
+        Self {
+            decode_padding_mode,
+            ..self
+        }
+    }
+
+    /// Set whether decoding rejects a final symbol whose unused low bits are
+    /// not zero, so that every byte sequence has exactly one accepted encoding.
+    pub const fn with_canonical(self, canonical: bool) -> Self {
+        Self { canonical, ..self }
+    }
+
+    /// Set whether decoding skips ASCII whitespace (spaces, tabs, line breaks)
+    /// anywhere in the input, as found in wrapped MIME and PEM payloads.
+    /// Padding is still validated against the remaining symbols.
+    pub const fn with_ignore_whitespace(self, ignore_whitespace: bool) -> Self {
+        Self {
+            ignore_whitespace,
+            ..self
+        }
+    }
+
+    /// Set how encoded output is broken into lines, or `None` for a single line.
+    pub const fn with_line_wrap(self, line_wrap: Option<LineWrap>) -> Self {
+        Self { line_wrap, ..self }
+    }
+
+    /// The alphabet used by this engine.
+    pub const fn alphabet(&self) -> &Alphabet {
+        &self.alphabet
+    }
+
+    /// Whether encoding appends `=` padding.
+    pub const fn encode_padding(&self) -> bool {
+        self.encode_padding
+    }
+
+    /// How decoding validates trailing `=` padding.
+    pub const fn decode_padding_mode(&self) -> DecodePaddingMode {
+        self.decode_padding_mode
+    }
+
+    /// Whether decoding rejects non-zero trailing bits.
+    pub const fn canonical(&self) -> bool {
+        self.canonical
+    }
+
+    /// Whether decoding skips ASCII whitespace.
+    pub const fn ignore_whitespace(&self) -> bool {
+        self.ignore_whitespace
+    }
+
+    /// How encoded output is broken into lines.
+    pub const fn line_wrap(&self) -> Option<LineWrap> {
+        self.line_wrap
+    }
+
+    /// Calculate the length of the output of encoding `input_len` bytes with
+    /// this engine, including padding and line endings.
+    /// Returns `None` if the length does not fit in a `usize`.
+    pub const fn encoded_len(&self, input_len: usize) -> Option<usize> {
+        let Some(encoded_len) = encoded_len(input_len, self.encode_padding) else {
+            return None;
+        };
+        match self.line_wrap {
+            Some(line_wrap) => line_wrap.wrapped_len(encoded_len),
+            None => Some(encoded_len),
+        }
+    }
+
+    /// Encode input bytes into a base64 string.
+    pub fn encode(&self, input: &[u8]) -> String {
+        let mut output = String::new();
+        encode_to_string(input, &mut output, self);
+        output
+    }
+
+    /// Encode input bytes and append the base64 output to `output`.
+    pub fn encode_to_string(&self, input: &[u8], output: &mut String) {
+        encode_to_string(input, output, self)
+    }
+
+    /// Encode input bytes into a caller-provided buffer.
+    /// Returns the number of bytes written.
+    pub fn encode_slice(&self, input: &[u8], output: &mut [u8]) -> Result<usize, EncodeSliceError> {
+        encode_slice(input, output, self)
+    }
+
+    /// Decode input base64 bytes into original bytes.
+    pub fn decode(&self, input: &[u8]) -> Result<Vec<u8>, DecodeError> {
+        let mut output = Vec::new();
+        decode_to_vec(input, &mut output, self)?;
+        Ok(output)
+    }
+
+    /// Decode input base64 bytes and append the original bytes to `output`.
+    /// Returns the number of bytes appended.
+    pub fn decode_to_vec(&self, input: &[u8], output: &mut Vec<u8>) -> Result<usize, DecodeError> {
+        decode_to_vec(input, output, self)
+    }
+
+    /// Decode input base64 bytes into a caller-provided buffer.
+    /// Returns the number of bytes written.
+    pub fn decode_slice(&self, input: &[u8], output: &mut [u8]) -> Result<usize, DecodeSliceError> {
+        decode_slice(input, output, self)
+    }
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_safe_roundtrip() {
+        let input = [0xfb, 0xff, 0xbf];
+        assert_eq!(Engine::STANDARD.encode(&input), "+/+/");
+        assert_eq!(Engine::URL_SAFE.encode(&input), "-_-_");
+        assert_eq!(Engine::URL_SAFE.decode(b"-_-_").unwrap(), input);
+        assert_eq!(
+            Engine::STANDARD.decode(b"-_-_"),
+            Err(DecodeError::InvalidByte {
+                offset: 0,
+                byte: b'-'
+            })
+        );
+    }
+
+    #[test]
+    fn test_no_pad_engines() {
+        assert_eq!(Engine::STANDARD_NO_PAD.encode(b"a"), "YQ");
+        assert_eq!(Engine::URL_SAFE_NO_PAD.encode(&[0xfb, 0xff]), "-_8");
+        assert_eq!(
+            Engine::URL_SAFE_NO_PAD.decode(b"-_8").unwrap(),
+            [0xfb, 0xff]
+        );
+        assert_eq!(
+            Engine::URL_SAFE_NO_PAD.decode(b"-_8="),
+            Err(DecodeError::WrongPadding { offset: 3 })
+        );
+    }
+
+    #[test]
+    fn test_wrapped_encoded_len() {
+        let wrap = LineWrap::new(4, LineEnding::CrLf);
+        assert_eq!(wrap.wrapped_len(0), Some(0));
+        assert_eq!(wrap.wrapped_len(4), Some(4));
+        assert_eq!(wrap.wrapped_len(5), Some(7));
+        assert_eq!(wrap.with_trailing_newline(true).wrapped_len(8), Some(12));
+        assert_eq!(Engine::MIME.encoded_len(57), Some(76));
+        assert_eq!(Engine::MIME.encoded_len(58), Some(82));
+        assert_eq!(Engine::PEM.encoded_len(48), Some(65));
+    }
+}
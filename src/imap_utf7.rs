@@ -0,0 +1,220 @@
+//! IMAP mailbox names in modified UTF-7 following
+//! [RFC 3501 §5.1.3](https://datatracker.ietf.org/doc/html/rfc3501#section-5.1.3).
+//!
+//! Printable ASCII stands for itself, except `&`, which is written as `&-`.
+//! Runs of other characters are written as UTF-16 in big-endian order,
+//! base64 encoded with the [`Alphabet::IMAP`] alphabet and no padding, and
+//! enclosed in `&` and `-`.
+//!
+//! Decoding accepts shifted printable ASCII and adjacent shifted runs, which
+//! a conforming encoder does not produce but some servers do.
+
+use std::fmt;
+
+use crate::alphabet::Alphabet;
+use crate::decode::{DecodeError, decode_bytes};
+use crate::encode::encode_bytes;
+use crate::engine::{DecodePaddingMode, Engine};
+
+/// Starts a shifted run of base64 encoded UTF-16.
+const SHIFT: u8 = b'&';
+
+/// Ends a shifted run.
+const UNSHIFT: u8 = b'-';
+
+/// Engine for shifted runs. Trailing bits must be zero, since each name has a
+/// single valid encoding.
+const ENGINE: Engine = Engine::new(Alphabet::IMAP)
+    .with_encode_padding(false)
+    .with_decode_padding_mode(DecodePaddingMode::Forbidden)
+    .with_canonical(true);
+
+/// Every variant carries the byte offset in the input where the problem was
+/// found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImapUtf7Error {
+    /// A `&` has no `-` ending its shifted run.
+    /// The offset points at the `&`.
+    UnterminatedShift { offset: usize },
+    /// A shifted run does not decode to valid UTF-16, such as an odd number of
+    /// bytes or an unpaired surrogate. The offset points at the `&`.
+    InvalidUtf16 { offset: usize },
+    /// A character outside printable ASCII, or invalid base64 in a shifted
+    /// run. Its offset is relative to the whole input.
+    Decode(DecodeError),
+}
+
+impl ImapUtf7Error {
+    /// The byte offset in the input where the problem was found.
+    pub const fn offset(&self) -> usize {
+        match *self {
+            Self::UnterminatedShift { offset } | Self::InvalidUtf16 { offset } => offset,
+            Self::Decode(ref err) => err.offset(),
+        }
+    }
+}
+
+impl fmt::Display for ImapUtf7Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnterminatedShift { offset } => {
+                write!(f, "'&' at offset {offset} has no closing '-'")
+            }
+            Self::InvalidUtf16 { offset } => {
+                write!(f, "shifted run at offset {offset} is not valid UTF-16")
+            }
+            Self::Decode(err) => write!(f, "invalid mailbox name: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ImapUtf7Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Decode(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `c` is written as itself, outside shifted runs.
+fn is_direct(c: char) -> bool {
+    matches!(c, ' '..='~')
+}
+
+/// Encode a mailbox name in modified UTF-7.
+pub fn encode(name: &str) -> String {
+    let mut output = String::with_capacity(name.len());
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == SHIFT as char {
+            output.push_str("&-");
+        } else if is_direct(c) {
+            output.push(c);
+        } else {
+            // Shift the whole run of characters that are not printable ASCII
+            let mut utf16 = Vec::new();
+            let mut units = [0u16; 2];
+            for c in
+                std::iter::once(c).chain(std::iter::from_fn(|| chars.next_if(|&c| !is_direct(c))))
+            {
+                for unit in c.encode_utf16(&mut units) {
+                    utf16.extend_from_slice(&unit.to_be_bytes());
+                }
+            }
+            let encoded = encode_bytes(&utf16, &ENGINE);
+            output.push(SHIFT as char);
+            // SAFETY: Base64 output is ASCII.
+            output.push_str(unsafe { std::str::from_utf8_unchecked(&encoded) });
+            output.push(UNSHIFT as char);
+        }
+    }
+    output
+}
+
+/// Decode a mailbox name from modified UTF-7.
+pub fn decode(input: &[u8]) -> Result<String, ImapUtf7Error> {
+    let mut output = String::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        let byte = input[i];
+        if byte != SHIFT {
+            if !is_direct(byte as char) {
+                return Err(ImapUtf7Error::Decode(DecodeError::InvalidByte {
+                    offset: i,
+                    byte,
+                }));
+            }
+            output.push(byte as char);
+            i += 1;
+            continue;
+        }
+
+        let run_len = input[i + 1..]
+            .iter()
+            .position(|&byte| byte == UNSHIFT)
+            .ok_or(ImapUtf7Error::UnterminatedShift { offset: i })?;
+        let run = &input[i + 1..i + 1 + run_len];
+        if run.is_empty() {
+            output.push(SHIFT as char);
+        } else {
+            let utf16 = decode_bytes(run, &ENGINE)
+                .map_err(|err| ImapUtf7Error::Decode(err.map_offset(|offset| i + 1 + offset)))?;
+            if !utf16.len().is_multiple_of(2) {
+                return Err(ImapUtf7Error::InvalidUtf16 { offset: i });
+            }
+            let units = utf16
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]));
+            for c in char::decode_utf16(units) {
+                output.push(c.map_err(|_| ImapUtf7Error::InvalidUtf16 { offset: i })?);
+            }
+        }
+        i += run_len + 2;
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let cases = [
+            ("INBOX", "INBOX"),
+            ("Tom & Jerry", "Tom &- Jerry"),
+            ("Entwürfe", "Entw&APw-rfe"),
+            ("~peter/mail/台北/日本語", "~peter/mail/&U,BTFw-/&ZeVnLIqe-"),
+            ("Ü ü", "&ANw- &APw-"),
+            ("📧", "&2D3c5w-"),
+            ("", ""),
+        ];
+        for (name, encoded) in cases {
+            assert_eq!(encode(name), encoded);
+            assert_eq!(decode(encoded.as_bytes()).unwrap(), name);
+        }
+    }
+
+    #[test]
+    fn test_decode_lenient() {
+        assert_eq!(decode(b"&AGEAYg-").unwrap(), "ab");
+        assert_eq!(decode(b"&APw-&APw-").unwrap(), "üü");
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(
+            decode(b"Sent&APw"),
+            Err(ImapUtf7Error::UnterminatedShift { offset: 4 })
+        );
+        assert_eq!(
+            decode(b"a&AP/-"),
+            Err(ImapUtf7Error::Decode(DecodeError::InvalidByte {
+                offset: 4,
+                byte: b'/'
+            }))
+        );
+        assert_eq!(
+            decode("Entwürfe".as_bytes()),
+            Err(ImapUtf7Error::Decode(DecodeError::InvalidByte {
+                offset: 4,
+                byte: 0xC3
+            }))
+        );
+        // A single byte, and a lone high surrogate
+        assert_eq!(
+            decode(b"x&AA-"),
+            Err(ImapUtf7Error::InvalidUtf16 { offset: 1 })
+        );
+        assert_eq!(
+            decode(b"&2D0-"),
+            Err(ImapUtf7Error::InvalidUtf16 { offset: 0 })
+        );
+        // Non-zero trailing bits
+        assert!(matches!(
+            decode(b"&APx-"),
+            Err(ImapUtf7Error::Decode(DecodeError::TrailingBits { .. }))
+        ));
+    }
+}
@@ -0,0 +1,101 @@
+//! Base64 encoding and decoding following [RFC 4648](https://datatracker.ietf.org/doc/html/rfc4648).
+//!
+//! The [`encode`] and [`decode`] functions work on raw bytes with the standard
+//! alphabet, while [`encode_string`] and [`decode_string`] are convenience
+//! wrappers for text. Other alphabets are selected through an [`Engine`].
+//! Large inputs can be encoded incrementally with an [`EncoderWriter`] and
+//! decoded incrementally with a [`DecoderReader`]. Hot paths can avoid
+//! allocations with [`encode_slice`], [`decode_slice`] and the appending
+//! [`encode_to_string`] and [`decode_to_vec`] variants, with buffers sized by
+//! [`encoded_len`], [`decoded_len_estimate`] and [`decoded_len_exact`].
+//! Output can be broken into lines with a [`LineWrap`], with presets for
+//! MIME ([`Engine::MIME`]) and PEM ([`Engine::PEM`]). Whole PEM documents with
+//! their labels and headers are handled by the [`pem`] module, OpenPGP ASCII
+//! Armor with its CRC-24 checksum by the [`armor`] module, and RFC 2397
+//! `data:` URLs by the [`data_url`] module. Legacy uuencoded and xxencoded
+//! files are handled by the [`uuencode`] module, MIME quoted-printable by
+//! [`QuotedPrintable`], RFC 2047 encoded words in mail headers by the
+//! [`encoded_word`] module, and IMAP mailbox names in modified UTF-7 by the
+//! [`imap_utf7`] module.
+//!
+//! Base32 and base32hex from the same RFC are provided by [`Base32`], which
+//! shares the error types and padding modes of the base64 codec and also has
+//! a z-base-32 alphabet. [`Crockford`] covers Crockford's base32 and ULIDs,
+//! with hyphens and an optional check symbol, and hex is provided by
+//! [`Base16`]. [`Base58`] covers Bitcoin-style base58 and Base58Check, and
+//! [`Base85`] covers Ascii85 and Z85. [`Base45`] implements RFC 9285 for QR
+//! code payloads. Any other radix, such as base36 and base62 identifiers, is
+//! handled by [`BaseN`] with an alphabet of 2 to 256 symbols.
+
+pub mod alphabet;
+pub mod armor;
+pub mod base16;
+pub mod base32;
+pub mod base45;
+pub mod base58;
+pub mod base85;
+pub mod base_n;
+pub mod data_url;
+pub mod decode;
+pub mod encode;
+pub mod encoded_word;
+pub mod engine;
+pub mod imap_utf7;
+pub mod pem;
+pub mod quoted_printable;
+pub mod read;
+mod sha256;
+pub mod uuencode;
+pub mod write;
+
+pub use alphabet::{Alphabet, AlphabetError};
+pub use armor::{Armor, ArmorError};
+pub use base_n::{BaseN, BaseNAlphabet};
+pub use base16::Base16;
+pub use base32::{Base32, Base32Alphabet, Crockford};
+pub use base45::Base45;
+pub use base58::{Base58, Base58Alphabet};
+pub use base85::Base85;
+pub use data_url::{DataUrl, DataUrlError};
+pub use decode::{
+    DecodeError, DecodeSliceError, decode_bytes, decode_slice, decode_string, decode_to_vec,
+    decoded_len_estimate, decoded_len_exact,
+};
+pub use encode::{
+    EncodeError, EncodeSliceError, encode_bytes, encode_slice, encode_string, encode_to_string,
+    encoded_len,
+};
+pub use encoded_word::EncodedWordError;
+pub use engine::{DecodePaddingMode, Engine, LineEnding, LineWrap};
+pub use imap_utf7::ImapUtf7Error;
+pub use pem::{Pem, PemError};
+pub use quoted_printable::QuotedPrintable;
+pub use read::DecoderReader;
+pub use uuencode::{UuDecoderReader, UuEncoderWriter, UuError, UuFile, Uuencode};
+pub use write::EncoderWriter;
+
+const N: u8 = 64;
+const PAD_CHAR: u8 = b'=';
+
+/// Encode input bytes into a base64 string using the standard alphabet.
+pub fn encode(input: &[u8]) -> String {
+    Engine::STANDARD.encode(input)
+}
+
+/// Decode input base64 bytes into original bytes using the standard alphabet.
+pub fn decode(input: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    Engine::STANDARD.decode(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let input = "Hello, 世界! 🦀".as_bytes();
+        let encoded = encode(input);
+        assert_eq!(encoded, "SGVsbG8sIOS4lueVjCEg8J+mgA==");
+        assert_eq!(decode(encoded.as_bytes()).unwrap(), input);
+    }
+}
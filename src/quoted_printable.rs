@@ -0,0 +1,444 @@
+//! Quoted-printable encoding following
+//! [RFC 2045 §6.7](https://datatracker.ietf.org/doc/html/rfc2045#section-6.7).
+//!
+//! Printable ASCII is kept as is and every other byte is written as `=XX`
+//! with two upper case hex digits. Encoded lines are at most 76 characters,
+//! with longer lines split by soft line breaks (`=` at the end of a line).
+//! In text mode, line breaks in the input are kept as line breaks; in binary
+//! mode, CR and LF are escaped like any other byte.
+
+use crate::decode::DecodeError;
+use crate::engine::LineEnding;
+
+/// Maximum length of an encoded line, excluding the line ending.
+const MAX_LINE_LEN: usize = 76;
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+/// A quoted-printable codec configuration: whether input line breaks are
+/// text or data, the line ending to write, and how strict decoding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotedPrintable {
+    binary: bool,
+    line_ending: LineEnding,
+    strict: bool,
+}
+
+impl QuotedPrintable {
+    /// Text mode with CRLF line endings, as used in email. Decoding is lenient.
+    pub const TEXT: QuotedPrintable = QuotedPrintable {
+        binary: false,
+        line_ending: LineEnding::CrLf,
+        strict: false,
+    };
+
+    /// Binary mode, which escapes CR and LF so the data round-trips exactly.
+    pub const BINARY: QuotedPrintable = QuotedPrintable::TEXT.with_binary(true);
+
+    /// Set whether input is binary data. In text mode, CRLF and LF in the
+    /// input are encoded as line breaks, and line breaks are decoded as the
+    /// codec's line ending. In binary mode, CR and LF are escaped as `=0D` and
+    /// `=0A`, and decoded line breaks are kept as they are.
+    pub const fn with_binary(self, binary: bool) -> Self {
+        Self { binary, ..self }
+    }
+
+    /// Set the line ending written for soft and hard line breaks.
+    pub const fn with_line_ending(self, line_ending: LineEnding) -> Self {
+        Self {
+            line_ending,
+            ..self
+        }
+    }
+
+    /// Set whether decoding rejects input an encoder following RFC 2045 would
+    /// not produce: malformed or lower case `=XX` escapes, bytes that should
+    /// have been escaped, bare CR, whitespace at the end of a line, and lines
+    /// longer than 76 characters. Lenient decoding keeps malformed escapes and
+    /// such bytes as they are, drops whitespace at the end of a line, and
+    /// accepts lines of any length.
+    pub const fn with_strict(self, strict: bool) -> Self {
+        Self { strict, ..self }
+    }
+
+    /// Whether input is binary data.
+    pub const fn binary(&self) -> bool {
+        self.binary
+    }
+
+    /// The line ending written for soft and hard line breaks.
+    pub const fn line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    /// Whether decoding rejects input not produced by a conforming encoder.
+    pub const fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Encode input bytes into a quoted-printable string.
+    pub fn encode(&self, input: &[u8]) -> String {
+        let mut output = String::new();
+        self.encode_to_string(input, &mut output);
+        output
+    }
+
+    /// Encode input bytes and append the quoted-printable output to `output`.
+    pub fn encode_to_string(&self, input: &[u8], output: &mut String) {
+        // SAFETY: Only printable ASCII, escapes and line endings are appended,
+        // so the string stays valid UTF-8.
+        let output = unsafe { output.as_mut_vec() };
+        let ending = self.line_ending.as_bytes();
+        let mut column = 0;
+        let mut i = 0;
+        while i < input.len() {
+            let byte = input[i];
+            if !self.binary {
+                let break_len = match &input[i..] {
+                    [b'\r', b'\n', ..] => 2,
+                    [b'\n', ..] => 1,
+                    _ => 0,
+                };
+                if break_len > 0 {
+                    output.extend_from_slice(ending);
+                    column = 0;
+                    i += break_len;
+                    continue;
+                }
+            }
+
+            // Whitespace must be escaped at the end of a line, where transports
+            // may strip it
+            let at_line_end = match input.get(i + 1) {
+                None => true,
+                Some(b'\r' | b'\n') => !self.binary,
+                Some(_) => false,
+            };
+            let literal = match byte {
+                b'=' => false,
+                b' ' | b'\t' => !at_line_end,
+                b'!'..=b'~' => true,
+                _ => false,
+            };
+            let len = if literal { 1 } else { 3 };
+
+            // Keep room for the `=` of a soft line break, unless this is the
+            // last character of the line
+            let limit = if at_line_end {
+                MAX_LINE_LEN
+            } else {
+                MAX_LINE_LEN - 1
+            };
+            if column + len > limit {
+                output.push(b'=');
+                output.extend_from_slice(ending);
+                column = 0;
+            }
+            if literal {
+                output.push(byte);
+            } else {
+                output.extend_from_slice(&[
+                    b'=',
+                    HEX_DIGITS[(byte >> 4) as usize],
+                    HEX_DIGITS[(byte & 0x0F) as usize],
+                ]);
+            }
+            column += len;
+            i += 1;
+        }
+    }
+
+    /// Decode quoted-printable input into the original bytes.
+    pub fn decode(&self, input: &[u8]) -> Result<Vec<u8>, DecodeError> {
+        let mut output = Vec::new();
+        self.decode_to_vec(input, &mut output)?;
+        Ok(output)
+    }
+
+    /// Decode quoted-printable input and append the original bytes to
+    /// `output`. Returns the number of bytes appended. Nothing is appended if
+    /// decoding fails.
+    pub fn decode_to_vec(&self, input: &[u8], output: &mut Vec<u8>) -> Result<usize, DecodeError> {
+        let start = output.len();
+        output.reserve(input.len());
+        let mut line_start = 0;
+        while line_start < input.len() {
+            let (line_end, break_len) = match input[line_start..].iter().position(|&b| b == b'\n') {
+                Some(pos) if pos > 0 && input[line_start + pos - 1] == b'\r' => {
+                    (line_start + pos - 1, 2)
+                }
+                Some(pos) => (line_start + pos, 1),
+                None => (input.len(), 0),
+            };
+
+            // Trailing whitespace may have been added in transport
+            let raw_line = &input[line_start..line_end];
+            let line = raw_line.trim_ascii_end();
+            let (content, soft_break) = match line.strip_suffix(b"=") {
+                Some(content) => (content, true),
+                None => (line, false),
+            };
+            let result = self
+                .decode_line(content, line_start, output)
+                .and_then(|()| self.check_line(raw_line, line.len(), line_start));
+            if let Err(err) = result {
+                output.truncate(start);
+                return Err(err);
+            }
+
+            if break_len > 0 && !soft_break {
+                if self.binary {
+                    output.extend_from_slice(&input[line_end..line_end + break_len]);
+                } else {
+                    output.extend_from_slice(self.line_ending.as_bytes());
+                }
+            }
+            line_start = line_end + break_len;
+        }
+        Ok(output.len() - start)
+    }
+
+    /// Decode the content of one line, without a soft line break, starting at
+    /// `offset` in the input.
+    fn decode_line(
+        &self,
+        line: &[u8],
+        offset: usize,
+        output: &mut Vec<u8>,
+    ) -> Result<(), DecodeError> {
+        let mut i = 0;
+        while i < line.len() {
+            let byte = line[i];
+            if byte == b'=' {
+                match (
+                    line.get(i + 1).and_then(|&b| self.hex_value(b)),
+                    line.get(i + 2).and_then(|&b| self.hex_value(b)),
+                ) {
+                    (Some(high), Some(low)) => {
+                        output.push(high << 4 | low);
+                        i += 3;
+                        continue;
+                    }
+                    _ if self.strict => {
+                        // Point at the first character that is not a hex digit
+                        let bad = if line.get(i + 1).and_then(|&b| self.hex_value(b)).is_none() {
+                            i + 1
+                        } else {
+                            i + 2
+                        };
+                        return Err(match line.get(bad) {
+                            Some(&byte) => DecodeError::InvalidByte {
+                                offset: offset + bad,
+                                byte,
+                            },
+                            None => DecodeError::InputLength {
+                                offset: offset + i,
+                                remainder: line.len() - i,
+                            },
+                        });
+                    }
+                    // Keep a malformed escape as literal text
+                    _ => {}
+                }
+            } else if self.strict && !matches!(byte, b' ' | b'\t' | b'!'..=b'~') {
+                return Err(DecodeError::InvalidByte {
+                    offset: offset + i,
+                    byte,
+                });
+            }
+            output.push(byte);
+            i += 1;
+        }
+        Ok(())
+    }
+
+    /// In strict mode, reject whitespace after the first `content_len` bytes of
+    /// a line starting at `offset` in the input, and lines longer than
+    /// [`MAX_LINE_LEN`]. The line excludes its line ending.
+    fn check_line(
+        &self,
+        line: &[u8],
+        content_len: usize,
+        offset: usize,
+    ) -> Result<(), DecodeError> {
+        if !self.strict {
+            return Ok(());
+        }
+        // Point at the trailing whitespace or the first character past the
+        // limit, whichever comes first
+        let bad = content_len.min(MAX_LINE_LEN);
+        match line.get(bad) {
+            Some(&byte) => Err(DecodeError::InvalidByte {
+                offset: offset + bad,
+                byte,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// The value of a hex digit, accepting lower case unless strict.
+    fn hex_value(&self, byte: u8) -> Option<u8> {
+        match byte {
+            b'0'..=b'9' => Some(byte - b'0'),
+            b'A'..=b'F' => Some(byte - b'A' + 10),
+            b'a'..=b'f' if !self.strict => Some(byte - b'a' + 10),
+            _ => None,
+        }
+    }
+}
+
+impl Default for QuotedPrintable {
+    fn default() -> Self {
+        Self::TEXT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let qp = QuotedPrintable::TEXT;
+        assert_eq!(qp.encode(b"a=b"), "a=3Db");
+        assert_eq!(qp.encode("café".as_bytes()), "caf=C3=A9");
+        assert_eq!(
+            qp.encode(b"line one \r\nline two\t"),
+            "line one=20\r\nline two=09"
+        );
+        assert_eq!(qp.encode(b"a\nb"), "a\r\nb");
+        assert_eq!(
+            qp.with_line_ending(LineEnding::Lf).encode(b"a\r\nb"),
+            "a\nb"
+        );
+        assert_eq!(QuotedPrintable::BINARY.encode(b"a\r\nb "), "a=0D=0Ab=20");
+    }
+
+    #[test]
+    fn test_soft_line_breaks() {
+        let qp = QuotedPrintable::TEXT;
+        let encoded = qp.encode(&[b'x'; 200]);
+        let lines: Vec<&str> = encoded.split("\r\n").collect();
+        assert_eq!(lines.len(), 3);
+        assert!(
+            lines[..2]
+                .iter()
+                .all(|line| line.len() == 76 && line.ends_with('='))
+        );
+        assert_eq!(qp.decode(encoded.as_bytes()).unwrap(), [b'x'; 200]);
+
+        // A line of exactly 76 characters needs no soft break, and escapes
+        // are never split
+        assert_eq!(qp.encode(&[b'x'; 76]), "x".repeat(76));
+        let encoded = qp.encode(&[0xFF; 30]);
+        assert!(encoded.split("\r\n").all(|line| line.len() <= 76));
+        assert!(encoded.starts_with(&format!("{}=\r\n=FF", "=FF".repeat(25))));
+        assert_eq!(qp.decode(encoded.as_bytes()).unwrap(), [0xFF; 30]);
+    }
+
+    #[test]
+    fn test_decode() {
+        let qp = QuotedPrintable::TEXT;
+        assert_eq!(
+            qp.decode(b"caf=C3=A9 =\r\nau lait  \r\nnext").unwrap(),
+            "café au lait\r\nnext".as_bytes()
+        );
+        assert_eq!(qp.decode(b"soft=\nbreak\n").unwrap(), b"softbreak\r\n");
+        assert_eq!(
+            QuotedPrintable::BINARY.decode(b"a=0D=0Ab=20").unwrap(),
+            b"a\r\nb "
+        );
+
+        let input: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let encoded = QuotedPrintable::BINARY.encode(&input);
+        assert_eq!(
+            QuotedPrintable::BINARY.decode(encoded.as_bytes()).unwrap(),
+            input
+        );
+    }
+
+    #[test]
+    fn test_strict_and_lenient() {
+        let lenient = QuotedPrintable::TEXT;
+        let strict = QuotedPrintable::TEXT.with_strict(true);
+        assert_eq!(lenient.decode(b"=c3=a9").unwrap(), "é".as_bytes());
+        assert_eq!(
+            strict.decode(b"=C3=a9"),
+            Err(DecodeError::InvalidByte {
+                offset: 4,
+                byte: b'a'
+            })
+        );
+        assert_eq!(lenient.decode(b"1=G0 = 2").unwrap(), b"1=G0 = 2");
+        assert_eq!(
+            strict.decode(b"1=G0"),
+            Err(DecodeError::InvalidByte {
+                offset: 2,
+                byte: b'G'
+            })
+        );
+        assert_eq!(
+            strict.decode(b"ok\nab=4"),
+            Err(DecodeError::InputLength {
+                offset: 5,
+                remainder: 2
+            })
+        );
+        assert_eq!(lenient.decode("é".as_bytes()).unwrap(), "é".as_bytes());
+        assert_eq!(
+            strict.decode("ab é".as_bytes()),
+            Err(DecodeError::InvalidByte {
+                offset: 3,
+                byte: 0xC3
+            })
+        );
+        assert_eq!(
+            strict.decode(b"a\rb"),
+            Err(DecodeError::InvalidByte {
+                offset: 1,
+                byte: b'\r'
+            })
+        );
+
+        // Whitespace at the end of a line, which an encoder would escape
+        assert_eq!(lenient.decode(b"a \r\nb\t").unwrap(), b"a\r\nb");
+        assert_eq!(
+            strict.decode(b"a \r\nb"),
+            Err(DecodeError::InvalidByte {
+                offset: 1,
+                byte: b' '
+            })
+        );
+        assert_eq!(
+            strict.decode(b"a=\r\nb\t"),
+            Err(DecodeError::InvalidByte {
+                offset: 5,
+                byte: b'\t'
+            })
+        );
+
+        // Lines longer than 76 characters, counting a soft line break
+        assert_eq!(lenient.decode(&[b'x'; 77]).unwrap(), [b'x'; 77]);
+        assert_eq!(
+            strict.decode(&[b'x'; 77]),
+            Err(DecodeError::InvalidByte {
+                offset: 76,
+                byte: b'x'
+            })
+        );
+        let encoded = format!("{}=\r\nx", "x".repeat(76));
+        assert_eq!(
+            strict.decode(encoded.as_bytes()),
+            Err(DecodeError::InvalidByte {
+                offset: 76,
+                byte: b'='
+            })
+        );
+        let encoded = lenient.encode(&[b'x'; 200]);
+        assert_eq!(strict.decode(encoded.as_bytes()).unwrap(), [b'x'; 200]);
+
+        let mut output = b"prefix:".to_vec();
+        assert!(strict.decode_to_vec(b"ok\n=ZZ", &mut output).is_err());
+        assert_eq!(output, b"prefix:");
+    }
+}
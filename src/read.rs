@@ -0,0 +1,238 @@
+use std::io::{self, Read};
+
+use crate::PAD_CHAR;
+use crate::decode::{DecodeError, decode_into};
+use crate::engine::Engine;
+
+/// Size of the buffer holding base64 input read from the inner reader.
+/// Must be a multiple of 4 so it always holds whole encoded chunks.
+const BUF_SIZE: usize = 1024;
+
+/// A reader that pulls base64 text from an inner reader and yields the decoded
+/// bytes, using a fixed amount of memory.
+///
+/// The final chunk is only decoded once the inner reader reaches end of file,
+/// so padding and trailing bits are validated exactly like [`decode_bytes`].
+/// Whitespace is dropped as it is read if the engine ignores whitespace.
+/// Invalid input is reported as an `io::Error` of kind
+/// [`io::ErrorKind::InvalidData`] wrapping a [`DecodeError`] whose offset is
+/// the absolute position of the offending symbol in the stream.
+///
+/// [`decode_bytes`]: crate::decode_bytes
+pub struct DecoderReader<R: Read> {
+    engine: Engine,
+    inner: R,
+    /// Base64 symbols read from the inner reader but not yet decoded.
+    input: [u8; BUF_SIZE],
+    input_len: usize,
+    /// Absolute offset in the stream of each buffered symbol, so errors can
+    /// point into the stream even after whitespace has been dropped.
+    positions: Box<[usize]>,
+    /// Absolute offset in the stream of the next byte read from `inner`.
+    stream_offset: usize,
+    /// Decoded bytes not yet returned to the caller.
+    output: [u8; BUF_SIZE / 4 * 3],
+    output_start: usize,
+    output_len: usize,
+    eof: bool,
+}
+
+impl<R: Read> DecoderReader<R> {
+    /// Create a reader that decodes base64 from `inner` with `engine`.
+    pub fn new(inner: R, engine: &Engine) -> Self {
+        Self {
+            engine: *engine,
+            inner,
+            input: [0; BUF_SIZE],
+            input_len: 0,
+            positions: vec![0; BUF_SIZE].into_boxed_slice(),
+            stream_offset: 0,
+            output: [0; BUF_SIZE / 4 * 3],
+            output_start: 0,
+            output_len: 0,
+            eof: false,
+        }
+    }
+
+    /// Return the inner reader. Buffered input that has not been decoded yet is lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Read from the inner reader until there is more than one chunk of input
+    /// buffered, the buffer is full, or the end of the stream is reached.
+    fn fill_input(&mut self) -> io::Result<()> {
+        while !self.eof && self.input_len <= 4 && self.input_len < BUF_SIZE {
+            let n = match self.inner.read(&mut self.input[self.input_len..]) {
+                Ok(0) => {
+                    self.eof = true;
+                    continue;
+                }
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            // Record where each new byte came from, dropping whitespace if needed
+            let mut kept = self.input_len;
+            for i in self.input_len..self.input_len + n {
+                let byte = self.input[i];
+                if !(self.engine.ignore_whitespace() && byte.is_ascii_whitespace()) {
+                    self.input[kept] = byte;
+                    self.positions[kept] = self.stream_offset;
+                    kept += 1;
+                }
+                self.stream_offset += 1;
+            }
+            self.input_len = kept;
+        }
+        Ok(())
+    }
+
+    /// Decode the next block of buffered input into the output buffer.
+    /// Returns `false` once the stream is exhausted.
+    fn decode_block(&mut self) -> io::Result<bool> {
+        self.fill_input()?;
+        let block_len = if self.eof {
+            // The rest of the stream is the final block and may be padded
+            if self.input_len == 0 {
+                return Ok(false);
+            }
+            self.input_len
+        } else {
+            // Hold back the last chunk since it may be the padded final chunk
+            (self.input_len - 1) / 4 * 4
+        };
+
+        let block = &self.input[..block_len];
+        let decoded = if !self.eof
+            && let Some(offset) = block.iter().position(|&b| b == PAD_CHAR)
+        {
+            // Padding followed by more input
+            Err(DecodeError::WrongPadding { offset })
+        } else {
+            decode_into(block, &mut self.output, &self.engine)
+        };
+        let decoded_len = decoded.map_err(|err| {
+            // Errors past the last buffered symbol point at the end of the stream
+            let err = err.map_offset(|offset| {
+                self.positions[..self.input_len]
+                    .get(offset)
+                    .copied()
+                    .unwrap_or(self.stream_offset)
+            });
+            io::Error::new(io::ErrorKind::InvalidData, err)
+        })?;
+
+        self.output_start = 0;
+        self.output_len = decoded_len;
+        self.input.copy_within(block_len..self.input_len, 0);
+        self.positions.copy_within(block_len..self.input_len, 0);
+        self.input_len -= block_len;
+        Ok(true)
+    }
+}
+
+impl<R: Read> Read for DecoderReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        while self.output_start == self.output_len {
+            if !self.decode_block()? {
+                return Ok(0);
+            }
+        }
+        let n = buf.len().min(self.output_len - self.output_start);
+        buf[..n].copy_from_slice(&self.output[self.output_start..self.output_start + n]);
+        self.output_start += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A reader that returns at most `n` bytes per call.
+    struct ShortReader<'a> {
+        data: &'a [u8],
+        n: usize,
+    }
+
+    impl Read for ShortReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.n.min(buf.len()).min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    fn read_all(data: &[u8], n: usize, engine: &Engine) -> io::Result<Vec<u8>> {
+        let mut reader = DecoderReader::new(ShortReader { data, n }, engine);
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output)?;
+        Ok(output)
+    }
+
+    #[test]
+    fn test_decoder_reader_matches_decode() {
+        let input: Vec<u8> = (0..=255).cycle().take(5000).collect();
+        for engine in [Engine::STANDARD, Engine::URL_SAFE_NO_PAD] {
+            let encoded = engine.encode(&input);
+            for n in [1, 3, 4, 5, 1000, 10000] {
+                assert_eq!(read_all(encoded.as_bytes(), n, &engine).unwrap(), input);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decoder_reader_error_offset() {
+        let mut encoded = Engine::STANDARD.encode(&[0; 3000]).into_bytes();
+        encoded[2500] = b'!';
+        let err = read_all(&encoded, 7, &Engine::STANDARD).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let inner = err.into_inner().unwrap().downcast::<DecodeError>().unwrap();
+        assert_eq!(
+            *inner,
+            DecodeError::InvalidByte {
+                offset: 2500,
+                byte: b'!'
+            }
+        );
+    }
+
+    #[test]
+    fn test_decoder_reader_padding_in_middle() {
+        let err = read_all(b"YQ==YWFh", 2, &Engine::STANDARD).unwrap_err();
+        let inner = err.into_inner().unwrap().downcast::<DecodeError>().unwrap();
+        assert_eq!(*inner, DecodeError::WrongPadding { offset: 2 });
+    }
+
+    #[test]
+    fn test_decoder_reader_ignoring_whitespace() {
+        let input: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let encoded = Engine::STANDARD.encode(&input);
+        let wrapped: Vec<u8> = encoded
+            .as_bytes()
+            .chunks(76)
+            .flat_map(|line| line.iter().chain(b"\r\n"))
+            .copied()
+            .collect();
+        let engine = Engine::STANDARD.with_ignore_whitespace(true);
+        for n in [1, 5, 77, 10000] {
+            assert_eq!(read_all(&wrapped, n, &engine).unwrap(), input);
+        }
+
+        let err = read_all(b"YWFh\n\nYW!h", 3, &engine).unwrap_err();
+        let inner = err.into_inner().unwrap().downcast::<DecodeError>().unwrap();
+        assert_eq!(
+            *inner,
+            DecodeError::InvalidByte {
+                offset: 8,
+                byte: b'!'
+            }
+        );
+    }
+}
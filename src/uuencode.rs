@@ -0,0 +1,850 @@
+//! uuencode and xxencode, the line-oriented encodings of `uuencode(1)` and
+//! `xxencode(1)` used by old Unix archives and mail attachments.
+//!
+//! A file is wrapped between a `begin <mode> <name>` line, with the mode in
+//! octal, and an `end` line. Each body line starts with a symbol giving the
+//! number of bytes on the line (at most 45 when encoding), followed by 4
+//! symbols for every 3 bytes. A line declaring zero bytes ends the body. The
+//! two encodings share this layout and differ only in their symbols.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use crate::decode::DecodeError;
+
+/// Number of bytes encoded on each full line, as written by `uuencode(1)`.
+const LINE_BYTES: usize = 45;
+
+/// Most bytes a line can declare with its 6-bit length symbol.
+const MAX_LINE_BYTES: usize = 63;
+
+/// Size of the buffer holding input read by [`UuDecoderReader`]. Longer
+/// lines are cut off, which never affects a valid body line.
+const BUF_SIZE: usize = 1024;
+
+/// Number of full lines encoded in a single `write` call.
+const LINES_PER_WRITE: usize = 16;
+
+const BEGIN: &[u8] = b"begin ";
+const END: &[u8] = b"end";
+
+const XX_SYMBOLS: &[u8; 64] = b"+-0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Variant {
+    Uu,
+    Xx,
+}
+
+/// A uuencode or xxencode codec for the body lines between `begin` and `end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Uuencode {
+    variant: Variant,
+}
+
+impl Uuencode {
+    /// uuencode, which writes the 6-bit values 1 to 63 as the characters `!`
+    /// to `_` and 0 as `` ` ``. Decoding also accepts a space for 0.
+    pub const UU: Uuencode = Uuencode {
+        variant: Variant::Uu,
+    };
+
+    /// xxencode, which only uses `+`, `-`, digits and letters so that it
+    /// survives character set translation.
+    pub const XX: Uuencode = Uuencode {
+        variant: Variant::Xx,
+    };
+
+    /// Encode input bytes as body lines of up to 45 bytes, followed by the
+    /// zero-length line that ends the body. Every line ends with LF.
+    pub fn encode(&self, input: &[u8]) -> String {
+        let mut output = String::new();
+        self.encode_to_string(input, &mut output);
+        output
+    }
+
+    /// Encode input bytes as body lines and append them to `output`.
+    pub fn encode_to_string(&self, input: &[u8], output: &mut String) {
+        // SAFETY: Only ASCII symbols and line endings are appended, so the
+        // string stays valid UTF-8.
+        let output_bytes = unsafe { output.as_mut_vec() };
+        for line in input.chunks(LINE_BYTES) {
+            self.encode_line(line, output_bytes);
+        }
+        self.encode_line(&[], output_bytes);
+    }
+
+    /// Decode body lines up to the zero-length line or `end` line that ends
+    /// the body, or to the end of the input. Lines may end with LF or CRLF.
+    pub fn decode(&self, input: &[u8]) -> Result<Vec<u8>, DecodeError> {
+        let mut output = Vec::new();
+        self.decode_to_vec(input, &mut output)?;
+        Ok(output)
+    }
+
+    /// Decode body lines and append the original bytes to `output`.
+    /// Returns the number of bytes appended. Nothing is appended if decoding fails.
+    pub fn decode_to_vec(&self, input: &[u8], output: &mut Vec<u8>) -> Result<usize, DecodeError> {
+        let start = output.len();
+        match self.decode_body(&mut lines(input), output) {
+            Ok(_) => Ok(output.len() - start),
+            Err(err) => {
+                output.truncate(start);
+                Err(err)
+            }
+        }
+    }
+
+    /// The symbol for a 6-bit value.
+    fn symbol(&self, value: u8) -> u8 {
+        match self.variant {
+            Variant::Uu if value == 0 => b'`',
+            Variant::Uu => b' ' + value,
+            Variant::Xx => XX_SYMBOLS[value as usize],
+        }
+    }
+
+    /// The 6-bit value of a symbol, or `None` if it is not a valid symbol.
+    fn value(&self, symbol: u8) -> Option<u8> {
+        match self.variant {
+            Variant::Uu => matches!(symbol, b' '..=b'`').then(|| (symbol - b' ') & 0x3F),
+            Variant::Xx => XX_SYMBOLS
+                .iter()
+                .position(|&s| s == symbol)
+                .map(|index| index as u8),
+        }
+    }
+
+    /// Append one encoded line of at most 63 bytes, with its LF ending.
+    fn encode_line(&self, line: &[u8], output: &mut Vec<u8>) {
+        output.push(self.symbol(line.len() as u8));
+        for chunk in line.chunks(3) {
+            let mut group = [0u8; 3];
+            group[..chunk.len()].copy_from_slice(chunk);
+            let value = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+            for shift in [18, 12, 6, 0] {
+                output.push(self.symbol((value >> shift & 0x3F) as u8));
+            }
+        }
+        output.push(b'\n');
+    }
+
+    /// Decode one body line without its line ending into `output`, returning
+    /// the number of bytes it declares. Error offsets are relative to the line.
+    fn decode_line(
+        &self,
+        line: &[u8],
+        output: &mut [u8; MAX_LINE_BYTES],
+    ) -> Result<usize, DecodeError> {
+        // An empty line ends the body like a zero-length one
+        let Some((&length_symbol, symbols)) = line.split_first() else {
+            return Ok(0);
+        };
+        let len = self.value(length_symbol).ok_or(DecodeError::InvalidByte {
+            offset: 0,
+            byte: length_symbol,
+        })? as usize;
+
+        // Encoders that strip trailing spaces may drop the zero symbols padding
+        // the last group, but every symbol holding data must be present.
+        // Symbols past the declared length, such as old per-line checksums,
+        // are ignored.
+        if symbols.len() < (len * 4).div_ceil(3) {
+            return Err(DecodeError::InputLength {
+                offset: line.len(),
+                remainder: symbols.len() % 4,
+            });
+        }
+        for (i, bytes) in output[..len].chunks_mut(3).enumerate() {
+            let mut value = 0u32;
+            for offset in 1 + 4 * i..5 + 4 * i {
+                let digit = match line.get(offset) {
+                    Some(&byte) => self
+                        .value(byte)
+                        .ok_or(DecodeError::InvalidByte { offset, byte })?,
+                    None => 0,
+                };
+                value = value << 6 | digit as u32;
+            }
+            bytes.copy_from_slice(&value.to_be_bytes()[1..1 + bytes.len()]);
+        }
+        Ok(len)
+    }
+
+    /// Decode body lines from `lines` into `output` until a zero-length line
+    /// or an `end` line, which is returned, or until the lines run out.
+    fn decode_body<'a>(
+        &self,
+        lines: &mut impl Iterator<Item = (usize, &'a [u8])>,
+        output: &mut Vec<u8>,
+    ) -> Result<Option<&'a [u8]>, DecodeError> {
+        let mut bytes = [0u8; MAX_LINE_BYTES];
+        for (offset, line) in lines {
+            if is_end(line) {
+                return Ok(Some(line));
+            }
+            match self
+                .decode_line(line, &mut bytes)
+                .map_err(|err| err.map_offset(|line_offset| offset + line_offset))?
+            {
+                0 => return Ok(Some(line)),
+                len => output.extend_from_slice(&bytes[..len]),
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl Default for Uuencode {
+    fn default() -> Self {
+        Self::UU
+    }
+}
+
+/// A uuencoded or xxencoded file: its permission mode, name and contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UuFile {
+    mode: u32,
+    name: String,
+    data: Vec<u8>,
+}
+
+impl UuFile {
+    /// Create a file with the given permission mode (e.g. `0o644`), name and
+    /// contents.
+    pub fn new(mode: u32, name: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            mode,
+            name: name.into(),
+            data: data.into(),
+        }
+    }
+
+    /// The permission mode from the `begin` line.
+    pub fn mode(&self) -> u32 {
+        self.mode
+    }
+
+    /// The file name from the `begin` line.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The decoded contents.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consume the file and return the decoded contents.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Encode the file with its `begin` and `end` lines, every line
+    /// terminated by LF.
+    pub fn encode(&self, codec: &Uuencode) -> String {
+        let mut output = header(self.mode, &self.name);
+        codec.encode_to_string(&self.data, &mut output);
+        output.push_str("end\n");
+        output
+    }
+}
+
+/// Every variant except [`UuError::MissingBegin`] carries the byte offset in
+/// the input where the problem was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UuError {
+    /// The input contains no `begin` line.
+    MissingBegin,
+    /// The `begin` line at `offset` has no octal mode or no file name.
+    InvalidHeader { offset: usize },
+    /// The file starting at `offset` has no `end` line after its body.
+    MissingEnd { offset: usize },
+    /// A body line is invalid. Its offset is relative to the whole input.
+    Decode(DecodeError),
+}
+
+impl UuError {
+    /// The byte offset in the input where the problem was found, if any.
+    pub const fn offset(&self) -> Option<usize> {
+        match *self {
+            Self::MissingBegin => None,
+            Self::InvalidHeader { offset } | Self::MissingEnd { offset } => Some(offset),
+            Self::Decode(ref err) => Some(err.offset()),
+        }
+    }
+}
+
+impl fmt::Display for UuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingBegin => f.write_str("no begin line found"),
+            Self::InvalidHeader { offset } => {
+                write!(f, "invalid begin line at offset {offset}")
+            }
+            Self::MissingEnd { offset } => {
+                write!(f, "file at offset {offset} has no end line")
+            }
+            Self::Decode(err) => write!(f, "invalid body: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for UuError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Decode(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Parse the first file in `input`, ignoring any text before its `begin`
+/// line and after its `end` line.
+pub fn parse(input: &[u8], codec: &Uuencode) -> Result<UuFile, UuError> {
+    let mut lines = lines(input);
+    let (begin_offset, mode, name) = loop {
+        let Some((offset, line)) = lines.next() else {
+            return Err(UuError::MissingBegin);
+        };
+        if let Some(header) = parse_header(line, offset) {
+            let (mode, name) = header?;
+            break (offset, mode, name);
+        }
+    };
+
+    let mut data = Vec::new();
+    let ended = match codec
+        .decode_body(&mut lines, &mut data)
+        .map_err(UuError::Decode)?
+    {
+        Some(line) if is_end(line) => true,
+        // The zero-length line must be followed by `end`
+        Some(_) => lines.next().is_some_and(|(_, line)| is_end(line)),
+        None => false,
+    };
+    if !ended {
+        return Err(UuError::MissingEnd {
+            offset: begin_offset,
+        });
+    }
+    Ok(UuFile { mode, name, data })
+}
+
+/// Split the input into lines without their line endings, each with the byte
+/// offset of its first character.
+fn lines(input: &[u8]) -> impl Iterator<Item = (usize, &[u8])> {
+    input
+        .split_inclusive(|&byte| byte == b'\n')
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some((start, trim_line_ending(line)))
+        })
+}
+
+fn trim_line_ending(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+/// Whether `line` is the `end` line, tolerating trailing whitespace.
+fn is_end(line: &[u8]) -> bool {
+    line.trim_ascii_end() == END
+}
+
+/// The `begin` line for a file, with its LF ending.
+fn header(mode: u32, name: &str) -> String {
+    format!("begin {mode:03o} {name}\n")
+}
+
+/// Parse a `begin <mode> <name>` line, or return `None` if `line` is not one.
+fn parse_header(line: &[u8], offset: usize) -> Option<Result<(u32, String), UuError>> {
+    let rest = line.strip_prefix(BEGIN)?;
+    let header = std::str::from_utf8(rest)
+        .ok()
+        .and_then(|rest| rest.trim_start().split_once(' '))
+        .and_then(|(mode, name)| {
+            let mode = u32::from_str_radix(mode, 8).ok()?;
+            let name = name.trim_end();
+            (!name.is_empty()).then(|| (mode, name.to_string()))
+        });
+    Some(header.ok_or(UuError::InvalidHeader { offset }))
+}
+
+/// A writer that encodes everything written to it as a uuencoded or
+/// xxencoded file and forwards the encoded lines to an inner writer.
+///
+/// The `begin` line is written with the first encoded line. Bytes that do not
+/// fill a whole line are held back until more input arrives. Call
+/// [`UuEncoderWriter::finish`] to encode the final line and the `end` line
+/// and get the inner writer back. Dropping the writer finishes it as well,
+/// but ignores any errors.
+pub struct UuEncoderWriter<W: Write> {
+    codec: Uuencode,
+    /// `None` only after the writer has been finished.
+    inner: Option<W>,
+    /// Input bytes of the current line, encoded once the line is full.
+    line: [u8; LINE_BYTES],
+    line_len: usize,
+    /// Encoded lines waiting to be written to the inner writer.
+    output: Vec<u8>,
+}
+
+impl<W: Write> UuEncoderWriter<W> {
+    /// Create a writer that encodes a file with the given mode and name with
+    /// `codec` and writes it to `inner`.
+    pub fn new(inner: W, codec: &Uuencode, mode: u32, name: &str) -> Self {
+        Self {
+            codec: *codec,
+            inner: Some(inner),
+            line: [0; LINE_BYTES],
+            line_len: 0,
+            output: header(mode, name).into_bytes(),
+        }
+    }
+
+    /// Encode any remaining input, write the `end` line and all buffered
+    /// output, and return the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let result = self.write_final();
+        let inner = self.inner.take().expect("writer is not finished yet");
+        result.map(|()| inner)
+    }
+
+    /// A reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        self.inner.as_ref().expect("writer is not finished yet")
+    }
+
+    /// A mutable reference to the inner writer.
+    /// Writing to it directly will interleave with the encoded output.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.inner.as_mut().expect("writer is not finished yet")
+    }
+
+    /// Write all buffered encoded lines to the inner writer.
+    fn write_output(&mut self) -> io::Result<()> {
+        let inner = self.inner.as_mut().expect("writer is not finished yet");
+        while !self.output.is_empty() {
+            match inner.write(&self.output) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write encoded output",
+                    ));
+                }
+                Ok(n) => {
+                    self.output.drain(..n);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Encode the final partial line and the end of the file, and write out
+    /// everything that is buffered.
+    fn write_final(&mut self) -> io::Result<()> {
+        if self.line_len > 0 {
+            self.codec
+                .encode_line(&self.line[..self.line_len], &mut self.output);
+            self.line_len = 0;
+        }
+        self.codec.encode_line(&[], &mut self.output);
+        self.output.extend_from_slice(b"end\n");
+        self.write_output()
+    }
+}
+
+impl<W: Write> Write for UuEncoderWriter<W> {
+    /// Accept up to a few lines' worth of input. Encoded lines are written to
+    /// the inner writer at the start of the next call, or on flush/finish.
+    fn write(&mut self, input: &[u8]) -> io::Result<usize> {
+        if input.is_empty() {
+            return Ok(0);
+        }
+        // Make room by writing out what the previous call encoded.
+        // If this fails, no input has been consumed yet.
+        self.write_output()?;
+
+        let mut consumed = 0;
+        for _ in 0..LINES_PER_WRITE {
+            let len = (LINE_BYTES - self.line_len).min(input.len() - consumed);
+            self.line[self.line_len..self.line_len + len]
+                .copy_from_slice(&input[consumed..consumed + len]);
+            self.line_len += len;
+            consumed += len;
+            if self.line_len < LINE_BYTES {
+                break;
+            }
+            self.codec.encode_line(&self.line, &mut self.output);
+            self.line_len = 0;
+        }
+        Ok(consumed)
+    }
+
+    /// Write all complete encoded lines and flush the inner writer.
+    /// A partial final line is only written by [`UuEncoderWriter::finish`].
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_output()?;
+        self.get_mut().flush()
+    }
+}
+
+impl<W: Write> Drop for UuEncoderWriter<W> {
+    fn drop(&mut self) {
+        // Avoid writing again if a panic is already unwinding through a write
+        if self.inner.is_some() && !std::thread::panicking() {
+            let _ = self.write_final();
+        }
+    }
+}
+
+/// Where a [`UuDecoderReader`] is in the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Skipping text before the `begin` line.
+    Header,
+    /// Decoding body lines of the file that begins at the given offset.
+    Body { begin_offset: usize },
+    /// Expecting the `end` line after the zero-length line.
+    End { begin_offset: usize },
+    /// The `end` line has been read.
+    Done,
+}
+
+/// A reader that pulls a uuencoded or xxencoded file from an inner reader and
+/// yields its decoded contents, using a fixed amount of memory.
+///
+/// Text before the `begin` line is skipped, and reading stops at the `end`
+/// line. The mode and name of the file are available once reading has
+/// started. Invalid input is reported as an `io::Error` of kind
+/// [`io::ErrorKind::InvalidData`] wrapping a [`UuError`] whose offset is the
+/// absolute position of the problem in the stream.
+pub struct UuDecoderReader<R: Read> {
+    codec: Uuencode,
+    inner: R,
+    /// Input read from the inner reader but not yet split into lines.
+    input: [u8; BUF_SIZE],
+    input_len: usize,
+    /// Absolute offset in the stream of the first buffered byte.
+    input_offset: usize,
+    /// Whether the buffer starts in the middle of a line that was too long
+    /// to buffer, whose rest is skipped.
+    skipping: bool,
+    state: State,
+    header: Option<(u32, String)>,
+    /// Decoded bytes of the current line not yet returned to the caller.
+    output: [u8; MAX_LINE_BYTES],
+    output_start: usize,
+    output_len: usize,
+    eof: bool,
+}
+
+impl<R: Read> UuDecoderReader<R> {
+    /// Create a reader that decodes a file from `inner` with `codec`.
+    pub fn new(inner: R, codec: &Uuencode) -> Self {
+        Self {
+            codec: *codec,
+            inner,
+            input: [0; BUF_SIZE],
+            input_len: 0,
+            input_offset: 0,
+            skipping: false,
+            state: State::Header,
+            header: None,
+            output: [0; MAX_LINE_BYTES],
+            output_start: 0,
+            output_len: 0,
+            eof: false,
+        }
+    }
+
+    /// The permission mode from the `begin` line, once it has been read.
+    pub fn mode(&self) -> Option<u32> {
+        self.header.as_ref().map(|(mode, _)| *mode)
+    }
+
+    /// The file name from the `begin` line, once it has been read.
+    pub fn name(&self) -> Option<&str> {
+        self.header.as_ref().map(|(_, name)| name.as_str())
+    }
+
+    /// Return the inner reader. Buffered input that has not been decoded yet is lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Buffer input until it holds a complete line, and return the length of
+    /// the line including its ending, or `None` at the end of the stream.
+    /// A line that does not fit in the buffer is cut off.
+    fn next_line(&mut self) -> io::Result<Option<usize>> {
+        loop {
+            if let Some(pos) = self.input[..self.input_len]
+                .iter()
+                .position(|&byte| byte == b'\n')
+            {
+                if !self.skipping {
+                    return Ok(Some(pos + 1));
+                }
+                self.consume(pos + 1);
+                self.skipping = false;
+                continue;
+            }
+            if self.eof || self.input_len == BUF_SIZE {
+                if self.skipping {
+                    self.consume(self.input_len);
+                    if !self.eof {
+                        continue;
+                    }
+                }
+                if self.input_len == 0 {
+                    return Ok(None);
+                }
+                self.skipping = !self.eof;
+                return Ok(Some(self.input_len));
+            }
+
+            match self.inner.read(&mut self.input[self.input_len..]) {
+                Ok(0) => self.eof = true,
+                Ok(n) => self.input_len += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Drop `len` bytes from the front of the input buffer.
+    fn consume(&mut self, len: usize) {
+        self.input.copy_within(len..self.input_len, 0);
+        self.input_len -= len;
+        self.input_offset += len;
+    }
+
+    /// Read lines until one decodes to some output.
+    /// Returns `false` once the `end` line has been read.
+    fn decode_next_line(&mut self) -> io::Result<bool> {
+        while self.state != State::Done {
+            let Some(len) = self.next_line()? else {
+                let err = match self.state {
+                    State::Header => UuError::MissingBegin,
+                    State::Body { begin_offset } | State::End { begin_offset } => {
+                        UuError::MissingEnd {
+                            offset: begin_offset,
+                        }
+                    }
+                    State::Done => unreachable!(),
+                };
+                return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+            };
+
+            let offset = self.input_offset;
+            let line = trim_line_ending(&self.input[..len]);
+            let result = match self.state {
+                State::Header => match parse_header(line, offset) {
+                    Some(Ok(header)) => {
+                        self.header = Some(header);
+                        self.state = State::Body {
+                            begin_offset: offset,
+                        };
+                        Ok(())
+                    }
+                    Some(Err(err)) => Err(err),
+                    None => Ok(()),
+                },
+                State::Body { .. } if is_end(line) => {
+                    self.state = State::Done;
+                    Ok(())
+                }
+                State::Body { begin_offset } => {
+                    match self.codec.decode_line(line, &mut self.output) {
+                        Ok(0) => {
+                            self.state = State::End { begin_offset };
+                            Ok(())
+                        }
+                        Ok(decoded_len) => {
+                            self.output_start = 0;
+                            self.output_len = decoded_len;
+                            Ok(())
+                        }
+                        Err(err) => Err(UuError::Decode(
+                            err.map_offset(|line_offset| offset + line_offset),
+                        )),
+                    }
+                }
+                State::End { begin_offset } => {
+                    if is_end(line) {
+                        self.state = State::Done;
+                        Ok(())
+                    } else {
+                        Err(UuError::MissingEnd {
+                            offset: begin_offset,
+                        })
+                    }
+                }
+                State::Done => unreachable!(),
+            };
+            result.map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            self.consume(len);
+            if self.output_start < self.output_len {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+impl<R: Read> Read for UuDecoderReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.output_start == self.output_len && !self.decode_next_line()? {
+            return Ok(0);
+        }
+        let n = buf.len().min(self.output_len - self.output_start);
+        buf[..n].copy_from_slice(&self.output[self.output_start..self.output_start + n]);
+        self.output_start += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        assert_eq!(Uuencode::UU.encode(b"Cat"), "#0V%T\n`\n");
+        assert_eq!(Uuencode::XX.encode(b"Cat"), "1Eq3o\n+\n");
+        assert_eq!(Uuencode::UU.decode(b"#0V%T\r\n`\r\n").unwrap(), b"Cat");
+
+        let input: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        for codec in [Uuencode::UU, Uuencode::XX] {
+            let encoded = codec.encode(&input);
+            assert!(encoded.lines().all(|line| line.len() <= 61));
+            assert_eq!(codec.decode(encoded.as_bytes()).unwrap(), input);
+        }
+
+        // Spaces for zero, and a missing padding symbol
+        assert_eq!(Uuencode::UU.decode(b"\"`` \n").unwrap(), [0, 0]);
+        assert_eq!(Uuencode::UU.decode(b"\"```\n").unwrap(), [0, 0]);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(
+            Uuencode::UU.decode(b"#0V%T\n#0V\n"),
+            Err(DecodeError::InputLength {
+                offset: 9,
+                remainder: 2
+            })
+        );
+        assert_eq!(
+            Uuencode::XX.decode(b"1Eq3o\n1E!3o\n"),
+            Err(DecodeError::InvalidByte {
+                offset: 8,
+                byte: b'!'
+            })
+        );
+        let mut output = b"prefix:".to_vec();
+        assert!(Uuencode::UU.decode_to_vec(b"#0V\n", &mut output).is_err());
+        assert_eq!(output, b"prefix:");
+    }
+
+    #[test]
+    fn test_parse_file() {
+        let input = b"Subject: cat\n\nbegin 644 cat.txt\n#0V%T\n`\nend\n";
+        let file = parse(input, &Uuencode::UU).unwrap();
+        assert_eq!(file, UuFile::new(0o644, "cat.txt", *b"Cat"));
+        assert_eq!(file.encode(&Uuencode::UU).as_bytes(), &input[14..]);
+
+        let file = UuFile::new(0o600, "my notes.txt", vec![7; 100]);
+        let encoded = file.encode(&Uuencode::XX);
+        assert!(encoded.starts_with("begin 600 my notes.txt\n"));
+        assert_eq!(parse(encoded.as_bytes(), &Uuencode::XX).unwrap(), file);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse(b"no file here\n", &Uuencode::UU),
+            Err(UuError::MissingBegin)
+        );
+        assert_eq!(
+            parse(b"x\nbegin 9x9 cat.txt\n", &Uuencode::UU),
+            Err(UuError::InvalidHeader { offset: 2 })
+        );
+        assert_eq!(
+            parse(b"begin 644 cat.txt\n#0V%T\n", &Uuencode::UU),
+            Err(UuError::MissingEnd { offset: 0 })
+        );
+        assert_eq!(
+            parse(b"begin 644 cat.txt\n#0V%T\n`\nmore\n", &Uuencode::UU),
+            Err(UuError::MissingEnd { offset: 0 })
+        );
+        assert_eq!(
+            parse(b"begin 644 cat.txt\n#0V%~\n`\nend\n", &Uuencode::UU),
+            Err(UuError::Decode(DecodeError::InvalidByte {
+                offset: 22,
+                byte: b'~'
+            }))
+        );
+        // A missing zero-length line is tolerated
+        assert_eq!(
+            parse(b"begin 644 cat.txt\n#0V%T\nend\n", &Uuencode::UU)
+                .unwrap()
+                .data(),
+            b"Cat"
+        );
+    }
+
+    #[test]
+    fn test_encoder_writer() {
+        let input: Vec<u8> = (0..=255).cycle().take(5000).collect();
+        let expected = UuFile::new(0o644, "data.bin", input.clone()).encode(&Uuencode::UU);
+        for piece_len in [1, 44, 45, 46, 5000] {
+            let mut writer = UuEncoderWriter::new(Vec::new(), &Uuencode::UU, 0o644, "data.bin");
+            for piece in input.chunks(piece_len) {
+                writer.write_all(piece).unwrap();
+            }
+            assert_eq!(writer.finish().unwrap(), expected.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_decoder_reader() {
+        let input: Vec<u8> = (0..=255).cycle().take(5000).collect();
+        let preamble = "x".repeat(3000);
+        let encoded = format!(
+            "{preamble}\r\n{}trailing text",
+            UuFile::new(0o755, "run.sh", input.clone()).encode(&Uuencode::XX)
+        );
+        let mut reader = UuDecoderReader::new(encoded.as_bytes(), &Uuencode::XX);
+        assert_eq!(reader.name(), None);
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).unwrap();
+        assert_eq!(output, input);
+        assert_eq!(reader.mode(), Some(0o755));
+        assert_eq!(reader.name(), Some("run.sh"));
+
+        let mut reader = UuDecoderReader::new(&b"begin 644 a\n#0V%~\n"[..], &Uuencode::UU);
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let inner = err.into_inner().unwrap().downcast::<UuError>().unwrap();
+        assert_eq!(
+            *inner,
+            UuError::Decode(DecodeError::InvalidByte {
+                offset: 16,
+                byte: b'~'
+            })
+        );
+
+        let mut reader = UuDecoderReader::new(&b"begin 644 a\n#0V%T\n"[..], &Uuencode::UU);
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        let inner = err.into_inner().unwrap().downcast::<UuError>().unwrap();
+        assert_eq!(*inner, UuError::MissingEnd { offset: 0 });
+    }
+}
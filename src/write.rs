@@ -0,0 +1,295 @@
+use std::io::{self, Write};
+
+use crate::encode::encode_into;
+use crate::engine::Engine;
+
+/// Maximum number of symbols encoded in a single `write` call.
+/// Must be a multiple of 4 so it always holds whole encoded chunks.
+const BUF_SIZE: usize = 1024;
+
+/// Maximum number of input bytes encoded in a single `write` call.
+const MAX_INPUT_LEN: usize = BUF_SIZE / 4 * 3;
+
+/// Size of the buffer holding encoded output not yet written to the inner
+/// writer. Line endings add at most two bytes per symbol, so this fits a full
+/// block of symbols wrapped at any width.
+const OUTPUT_SIZE: usize = BUF_SIZE * 3;
+
+/// A writer that base64-encodes everything written to it and forwards the
+/// encoded bytes to an inner writer, using a fixed amount of memory.
+///
+/// Bytes that do not yet form a complete 3-byte chunk are held back until more
+/// input arrives. Call [`EncoderWriter::finish`] to encode the final partial
+/// chunk (with padding if the engine asks for it) and get the inner writer
+/// back. Dropping the writer finishes it as well, but ignores any errors.
+///
+/// If the engine wraps lines, line endings are inserted across writes exactly
+/// as [`encode_bytes`] would insert them.
+///
+/// [`encode_bytes`]: crate::encode_bytes
+pub struct EncoderWriter<W: Write> {
+    engine: Engine,
+    /// `None` only after the writer has been finished.
+    inner: Option<W>,
+    /// Input bytes of an incomplete chunk, carried over between writes.
+    pending: [u8; 3],
+    pending_len: usize,
+    /// Encoded bytes waiting to be written to the inner writer.
+    output: [u8; OUTPUT_SIZE],
+    output_len: usize,
+    /// Number of symbols written on the current line.
+    column: usize,
+}
+
+impl<W: Write> EncoderWriter<W> {
+    /// Create a writer that encodes with `engine` and writes to `inner`.
+    pub fn new(inner: W, engine: &Engine) -> Self {
+        Self {
+            engine: *engine,
+            inner: Some(inner),
+            pending: [0; 3],
+            pending_len: 0,
+            output: [0; OUTPUT_SIZE],
+            output_len: 0,
+            column: 0,
+        }
+    }
+
+    /// Encode any remaining input, write all buffered output and return the
+    /// inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let result = self.write_final();
+        let inner = self.inner.take().expect("writer is not finished yet");
+        result.map(|()| inner)
+    }
+
+    /// A reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        self.inner.as_ref().expect("writer is not finished yet")
+    }
+
+    /// A mutable reference to the inner writer.
+    /// Writing to it directly will interleave with the encoded output.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.inner.as_mut().expect("writer is not finished yet")
+    }
+
+    /// Write all buffered encoded bytes to the inner writer.
+    fn write_output(&mut self) -> io::Result<()> {
+        let inner = self.inner.as_mut().expect("writer is not finished yet");
+        while self.output_len > 0 {
+            match inner.write(&self.output[..self.output_len]) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write encoded output",
+                    ));
+                }
+                Ok(n) => {
+                    self.output.copy_within(n..self.output_len, 0);
+                    self.output_len -= n;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Append encoded symbols to the output buffer, inserting line endings
+    /// if the engine wraps lines. A line ending is only added once another
+    /// symbol follows, so the last line is left open until finished.
+    fn push_encoded(&mut self, mut encoded: &[u8]) {
+        let Some(line_wrap) = self.engine.line_wrap() else {
+            self.output[self.output_len..self.output_len + encoded.len()].copy_from_slice(encoded);
+            self.output_len += encoded.len();
+            return;
+        };
+        while !encoded.is_empty() {
+            if self.column == line_wrap.width() {
+                self.push_line_ending();
+            }
+            let len = (line_wrap.width() - self.column).min(encoded.len());
+            self.output[self.output_len..self.output_len + len].copy_from_slice(&encoded[..len]);
+            self.output_len += len;
+            self.column += len;
+            encoded = &encoded[len..];
+        }
+    }
+
+    /// Append the engine's line ending to the output buffer and start a new line.
+    fn push_line_ending(&mut self) {
+        if let Some(line_wrap) = self.engine.line_wrap() {
+            let ending = line_wrap.ending().as_bytes();
+            self.output[self.output_len..self.output_len + ending.len()].copy_from_slice(ending);
+            self.output_len += ending.len();
+            self.column = 0;
+        }
+    }
+
+    /// Encode the final partial chunk and write out everything that is buffered.
+    fn write_final(&mut self) -> io::Result<()> {
+        // The output buffer may be full, so empty it before the last chunk
+        self.write_output()?;
+        if self.pending_len > 0 {
+            let mut encoded = [0u8; 4];
+            let encoded_len = encode_into(
+                &self.pending[..self.pending_len],
+                &mut encoded,
+                &self.engine,
+            );
+            self.push_encoded(&encoded[..encoded_len]);
+            self.pending_len = 0;
+        }
+        if self.column > 0
+            && self
+                .engine
+                .line_wrap()
+                .is_some_and(|line_wrap| line_wrap.trailing_newline())
+        {
+            self.push_line_ending();
+        }
+        self.write_output()
+    }
+}
+
+impl<W: Write> Write for EncoderWriter<W> {
+    /// Accept up to a buffer's worth of input. Encoded output is written to
+    /// the inner writer at the start of the next call, or on flush/finish.
+    fn write(&mut self, input: &[u8]) -> io::Result<usize> {
+        if input.is_empty() {
+            return Ok(0);
+        }
+        // Make room by writing out what the previous call encoded.
+        // If this fails, no input has been consumed yet.
+        self.write_output()?;
+
+        // Complete a chunk carried over from the previous call
+        let mut encoded = [0u8; BUF_SIZE];
+        let mut encoded_len = 0;
+        let mut consumed = 0;
+        if self.pending_len > 0 {
+            consumed = (3 - self.pending_len).min(input.len());
+            self.pending[self.pending_len..self.pending_len + consumed]
+                .copy_from_slice(&input[..consumed]);
+            self.pending_len += consumed;
+            if self.pending_len < 3 {
+                return Ok(consumed);
+            }
+            encoded_len = encode_into(&self.pending, &mut encoded, &self.engine);
+            self.pending_len = 0;
+        }
+
+        // Encode as many complete chunks as fit in the encoding buffer
+        let input = &input[consumed..];
+        let room = MAX_INPUT_LEN - encoded_len / 4 * 3;
+        let chunks_len = (input.len() / 3 * 3).min(room);
+        encoded_len += encode_into(
+            &input[..chunks_len],
+            &mut encoded[encoded_len..],
+            &self.engine,
+        );
+        self.push_encoded(&encoded[..encoded_len]);
+        consumed += chunks_len;
+
+        // Hold back a trailing partial chunk until more input arrives
+        let rest = &input[chunks_len..];
+        if rest.len() < 3 {
+            self.pending[..rest.len()].copy_from_slice(rest);
+            self.pending_len = rest.len();
+            consumed += rest.len();
+        }
+
+        Ok(consumed)
+    }
+
+    /// Write all complete encoded chunks and flush the inner writer.
+    /// A partial final chunk is only written by [`EncoderWriter::finish`].
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_output()?;
+        self.get_mut().flush()
+    }
+}
+
+impl<W: Write> Drop for EncoderWriter<W> {
+    fn drop(&mut self) {
+        // Avoid writing again if a panic is already unwinding through a write
+        if self.inner.is_some() && !std::thread::panicking() {
+            let _ = self.write_final();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoder_writer_matches_encode() {
+        let input: Vec<u8> = (0..=255).cycle().take(5000).collect();
+        for piece_len in [1, 2, 3, 4, 7, 100, 5000] {
+            let mut writer = EncoderWriter::new(Vec::new(), &Engine::STANDARD);
+            for piece in input.chunks(piece_len) {
+                writer.write_all(piece).unwrap();
+            }
+            let output = writer.finish().unwrap();
+            assert_eq!(output, Engine::STANDARD.encode(&input).into_bytes());
+        }
+    }
+
+    #[test]
+    fn test_encoder_writer_full_buffer_then_finish() {
+        // Fill the output buffer completely while leaving a partial chunk behind
+        let input = vec![b'a'; 1 + MAX_INPUT_LEN + 1];
+        let mut writer = EncoderWriter::new(Vec::new(), &Engine::STANDARD);
+        writer.write_all(&input[..1]).unwrap();
+        assert_eq!(writer.write(&input[1..]).unwrap(), input.len() - 1);
+        let output = writer.finish().unwrap();
+        assert_eq!(output, Engine::STANDARD.encode(&input).into_bytes());
+    }
+
+    #[test]
+    fn test_encoder_writer_padding() {
+        let mut writer = EncoderWriter::new(Vec::new(), &Engine::STANDARD);
+        writer.write_all(b"aaaa").unwrap();
+        assert_eq!(writer.finish().unwrap(), b"YWFhYQ==");
+
+        let mut writer = EncoderWriter::new(Vec::new(), &Engine::STANDARD_NO_PAD);
+        writer.write_all(b"aaaa").unwrap();
+        assert_eq!(writer.finish().unwrap(), b"YWFhYQ");
+    }
+
+    #[test]
+    fn test_encoder_writer_finishes_on_drop() {
+        let mut output = Vec::new();
+        {
+            let mut writer = EncoderWriter::new(&mut output, &Engine::STANDARD);
+            writer.write_all(b"aa").unwrap();
+        }
+        assert_eq!(output, b"YWE=");
+    }
+
+    #[test]
+    fn test_encoder_writer_line_wrap() {
+        use crate::engine::{LineEnding, LineWrap};
+
+        let input: Vec<u8> = (0..=255).cycle().take(3000).collect();
+        let engines = [
+            Engine::MIME,
+            Engine::PEM,
+            Engine::STANDARD_NO_PAD.with_line_wrap(Some(LineWrap::new(1, LineEnding::CrLf))),
+        ];
+        for engine in engines {
+            for len in [0, 1, 48, 57, 100, 3000] {
+                for piece_len in [1, 2, 7, 1000] {
+                    let mut writer = EncoderWriter::new(Vec::new(), &engine);
+                    for piece in input[..len].chunks(piece_len) {
+                        writer.write_all(piece).unwrap();
+                    }
+                    let output = writer.finish().unwrap();
+                    assert_eq!(output, engine.encode(&input[..len]).into_bytes());
+                }
+            }
+        }
+    }
+}